@@ -1,4 +1,6 @@
-use color_macros::{print_styled, println_styled};
+use std::fmt::Write as _;
+
+use color_macros::{pad_to, print_styled, println_styled, stream_colors, write_styled, Stream};
 
 fn main() {
     let colors: [&str; 16] = [
@@ -21,6 +23,16 @@ fn main() {
     ];
 
     for row in 0..=15 {
+        // `pad_to` aligns the labels by their visible width, which
+        // `format!("{:<14}", ...)` does not for styled text.
+        let mut label = String::new();
+        if stream_colors(Stream::Stdout) {
+            write_styled!(&mut label, colors[row], colors[row]);
+        } else {
+            label.push_str(colors[row]);
+        }
+        print!("{} ", pad_to(&label, 14));
+
         for col in 0..=15 {
             match (col, row) {
                 (15, r) => println_styled!(format!("{} on {}", colors[15], colors[r]), "[ + ]"),
@@ -3,9 +3,9 @@ use color_macros::{print_color256, println_color256};
 fn main() {
     for num in 0..=15 {
         match num {
-            7 | 15 => println_color256!(232, num, format!("[{:03}]", num)),
-            0 | 8 => print_color256!(7, num, format!("[{:03}]", num)),
-            _ => print_color256!(232, num, format!("[{:03}]", num)),
+            7 | 15 => println_color256!(232, num, "[{:03}]", num),
+            0 | 8 => print_color256!(7, num, "[{:03}]", num),
+            _ => print_color256!(232, num, "[{:03}]", num),
         }
     }
 
@@ -18,9 +18,9 @@ fn main() {
     {
         match num {
             27 | 63 | 99 | 135 | 171 | 207 => {
-                println_color256!(7, num, format!("[{:03}]", num));
+                println_color256!(7, num, "[{:03}]", num);
             }
-            _ => print_color256!(7, num, format!("[{:03}]", num)),
+            _ => print_color256!(7, num, "[{:03}]", num),
         }
     }
 
@@ -33,9 +33,9 @@ fn main() {
     {
         match num {
             39 | 75 | 111 | 147 | 183 | 219 => {
-                println_color256!(232, num, format!("[{:03}]", num));
+                println_color256!(232, num, "[{:03}]", num);
             }
-            _ => print_color256!(232, num, format!("[{:03}]", num)),
+            _ => print_color256!(232, num, "[{:03}]", num),
         }
     }
 
@@ -48,18 +48,18 @@ fn main() {
     {
         match num {
             51 | 87 | 123 | 159 | 195 | 231 => {
-                println_color256!(232, num, format!("[{:03}]", num));
+                println_color256!(232, num, "[{:03}]", num);
             }
-            _ => print_color256!(232, num, format!("[{:03}]", num)),
+            _ => print_color256!(232, num, "[{:03}]", num),
         }
     }
 
     for num in 232..=255 {
         match num {
-            232..=242 => print_color256!(7, num, format!("[{:03}]", num)),
-            243 => println_color256!(7, num, format!("[{:03}]", num)),
-            255 => println_color256!(232, num, format!("[{:03}]", num)),
-            _ => print_color256!(232, num, format!("[{:03}]", num)),
+            232..=242 => print_color256!(7, num, "[{:03}]", num),
+            243 => println_color256!(7, num, "[{:03}]", num),
+            255 => println_color256!(232, num, "[{:03}]", num),
+            _ => print_color256!(232, num, "[{:03}]", num),
         }
     }
 }
@@ -0,0 +1,18 @@
+use color_macros::{print_styled, println_styled, Color, Style};
+
+fn main() {
+    let total_cols: u32 = 80;
+
+    for col in 0..total_cols {
+        let hue = f64::from(col) * 360.0 / f64::from(total_cols);
+        let style = Style::new()
+            .fg(Color::from_hsl(hue + 180.0, 1.0, 0.5))
+            .on(Color::from_hsl(hue, 1.0, 0.5));
+
+        if col == (total_cols - 1) {
+            println_styled!(style, " ");
+        } else {
+            print_styled!(style, " ");
+        }
+    }
+}
@@ -1,25 +1,13 @@
-use color_macros::{print_rgb, println_rgb};
+use color_macros::{println_gradient, Color, Gradient, Interpolation};
 
 fn main() {
     let total_cols = 80;
 
-    for col in 0..total_cols {
-        let col = col as u32;
+    let stops = [Color::Rgb(255, 0, 0), Color::Rgb(0, 255, 0), Color::Rgb(0, 0, 255)];
+    let gradient = Gradient::new(stops).background();
 
-        let r = 255 - (col * 255 / total_cols);
-        let r: u8 = r.try_into().unwrap();
+    println_gradient!(gradient, "{:total_cols$}", "");
+    println_gradient!(gradient.interpolation(Interpolation::Oklab), "{:total_cols$}", "");
 
-        let g = col * 510 / total_cols;
-        let g = if g > 255 { 510 - g } else { g };
-        let g: u8 = g.try_into().unwrap();
-
-        let b = col * 255 / total_cols;
-        let b: u8 = b.try_into().unwrap();
-
-        if col == (total_cols - 1) {
-            println_rgb!((r, g, b), (255 - r, 255 - g, 255 - b), " ");
-        } else {
-            print_rgb!((r, g, b), (255 - r, 255 - g, 255 - b), " ");
-        }
-    }
+    println_gradient!((255, 0, 0), (0, 0, 255), "Gradient text, one write per line");
 }
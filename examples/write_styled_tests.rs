@@ -5,6 +5,7 @@ use std::fmt::Write;
 
 use color_macros::write_styled;
 
+#[allow(clippy::needless_range_loop, clippy::op_ref)]
 fn main() {
     const FG_COLORS: [(&str, &str); 17] = [
         ("current", ""),
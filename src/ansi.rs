@@ -0,0 +1,489 @@
+//! Functions for text that contains ANSI escape sequences.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use crate::{Attribute, Color, Style};
+
+/// Returns `text` without its ANSI escape sequences.
+///
+/// Removes CSI sequences (e.g. the "\x1b[1;31m" color codes written by the
+/// macros or cursor movements), OSC sequences (e.g. window titles and
+/// hyperlinks) and other two-byte escape sequences. An escape sequence that
+/// is cut off at the end of `text` is removed as well.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{strip_ansi, write_styled};
+///
+/// let mut buffer: Vec<u8> = vec![];
+/// write_styled!(&mut buffer, "bold red", "error");
+/// let text = String::from_utf8(buffer).unwrap();
+///
+/// assert_eq!(strip_ansi(&text), "error");
+/// assert_eq!(strip_ansi("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"), "link");
+/// ```
+#[must_use]
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\u{001b}') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+
+    for token in tokens(text) {
+        if let Token::Text(run) = token {
+            out.push_str(run);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Parses text containing SGR escape sequences (e.g. the output of the write
+/// macros) into spans of text and the style of each span.
+///
+/// This is the inverse of writing a styled text, which makes it possible to
+/// compare output by its styles rather than by its bytes. The parser
+/// understands:
+///
+/// * `0` (or no parameters), which resets the style;
+/// * the attribute codes `1` to `9` and the codes `22` to `29` that turn
+///   them off;
+/// * the basic and bright color codes, and `39`/`49` for the default colors;
+/// * 256 and RGB colors (`38;5;n` and `38;2;r;g;b`), including their forms
+///   with colon sub-parameters (`38:5:n` and `38:2::r:g:b`).
+///
+/// Unknown codes (including underline colors) are ignored, and other escape
+/// sequences are removed from the text. A `0` code returns to `Style::new()`,
+/// so text written after the reset code of a styled text has a plain style.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{parse_ansi, write_styled, Color, Style};
+///
+/// let mut buffer: Vec<u8> = vec![];
+/// write_styled!(&mut buffer, "bold red on blue", "error");
+/// buffer.extend_from_slice(b": \x1b[38;5;208mnot\x1b[39m found");
+/// let text = String::from_utf8(buffer).unwrap();
+///
+/// assert_eq!(
+///     parse_ansi(&text),
+///     [
+///         (Style::new().fg(Color::Red).on(Color::Blue).bold(), "error"),
+///         (Style::new(), ": "),
+///         (Style::new().fg(Color::Color256(208)), "not"),
+///         (Style::new(), " found"),
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn parse_ansi(text: &str) -> Vec<(Style, &str)> {
+    let mut spans = Vec::new();
+    let mut style = Style::new();
+
+    for token in tokens(text) {
+        match token {
+            Token::Text(run) => spans.push((style, run)),
+            Token::Escape(sequence) => style = apply_escape(style, sequence),
+        }
+    }
+    spans
+}
+
+/// Returns `style` with the escape sequence `sequence` applied to it, which
+/// only changes it if `sequence` is an SGR sequence.
+pub fn apply_escape(style: Style, sequence: &str) -> Style {
+    sequence
+        .strip_prefix("\u{001b}[")
+        .and_then(|rest| rest.strip_suffix('m'))
+        .map_or(style, |params| apply_sgr(style, params))
+}
+
+/// Returns `style` with the SGR parameters `params` (e.g. "1;38;5;208")
+/// applied to it.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let mut groups = params.split(';');
+
+    while let Some(group) = groups.next() {
+        let mut subparams = group.split(':').map(param);
+
+        let Some(code) = subparams.next().flatten() else {
+            continue;
+        };
+
+        match code {
+            0 => style = Style::new(),
+            // 6 is the rarely supported rapid blink.
+            1..=9 => {
+                if let Some(attr) = attribute(if code == 6 { 5 } else { code }) {
+                    style.attrs = style.attrs.with(attr);
+                }
+            }
+            22 => style.attrs = style.attrs.without(Attribute::Bold).without(Attribute::Dim),
+            23..=29 => {
+                if let Some(attr) = attribute(if code == 26 { 5 } else { code - 20 }) {
+                    style.attrs = style.attrs.without(attr);
+                }
+            }
+            30..=37 => style.fg = Some(Color::BASIC[usize::from(code - 30)]),
+            40..=47 => style.bg = Some(Color::BASIC[usize::from(code - 40)]),
+            90..=97 => style.fg = Some(Color::BRIGHT[usize::from(code - 90)]),
+            100..=107 => style.bg = Some(Color::BRIGHT[usize::from(code - 100)]),
+            // 58 sets the underline color, which a `Style` does not have.
+            38 | 48 | 58 => {
+                // The color is given by the sub-parameters of the code or, if
+                // it has none, by the parameters that follow it.
+                let color = if group.contains(':') {
+                    extended_color(subparams, true)
+                } else {
+                    extended_color(groups.by_ref().map(param), false)
+                };
+                match code {
+                    38 => style.fg = color.or(style.fg),
+                    48 => style.bg = color.or(style.bg),
+                    _ => {}
+                }
+            }
+            39 => style.fg = None,
+            49 => style.bg = None,
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Parses an SGR parameter, which is zero if it is empty (e.g. in "\x1b[;1m").
+fn param(text: &str) -> Option<u16> {
+    if text.is_empty() {
+        Some(0)
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// Returns the attribute whose ANSI code is `code`.
+fn attribute(code: u16) -> Option<Attribute> {
+    Attribute::ALL.into_iter().find(|attr| u16::from(attr.get_ansi_code()) == code)
+}
+
+/// Returns the 256 or RGB color of the parameters that follow a `38` or `48`
+/// code (e.g. "5;208" or "2;255;136;0"), consuming only the parameters of the
+/// color. The RGB form with colon sub-parameters may contain a color space ID
+/// before the red value (e.g. `2::255:136:0`).
+fn extended_color(
+    mut params: impl Iterator<Item = Option<u16>>,
+    subparams: bool,
+) -> Option<Color> {
+    let value = |param: Option<u16>| param.and_then(|n| u8::try_from(n).ok());
+
+    match params.next().flatten()? {
+        5 => value(params.next().flatten()).map(Color::Color256),
+        2 => {
+            let values: Vec<Option<u16>> = if subparams {
+                params.collect()
+            } else {
+                params.take(3).collect()
+            };
+            let [.., r, g, b] = values[..] else {
+                return None;
+            };
+            Some(Color::Rgb(value(r)?, value(g)?, value(b)?))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the escape sequence `sequence` is not cut off.
+pub fn is_complete(sequence: &str) -> bool {
+    let mut stripper = Stripper { state: State::Escape };
+    sequence.bytes().skip(1).all(|byte| !stripper.next(byte)) && stripper.state == State::Text
+}
+
+/// A run of text or an escape sequence in a text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// Text without escape sequences.
+    Text(&'a str),
+    /// An escape sequence, which may be cut off at the end of the text.
+    Escape(&'a str),
+}
+
+/// Returns an iterator over the runs of text and the escape sequences of
+/// `text`, using the same grammar as a `StripAnsiWriter`.
+pub fn tokens(text: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut pos = 0;
+
+    std::iter::from_fn(move || {
+        let rest = &text[pos..];
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        if !rest.starts_with('\u{001b}') {
+            let len = rest.find('\u{001b}').unwrap_or(rest.len());
+            pos += len;
+            return Some(Token::Text(&rest[..len]));
+        }
+
+        let bytes = rest.as_bytes();
+        let mut stripper = Stripper { state: State::Escape };
+        let mut len = 1;
+
+        while len < bytes.len() {
+            let byte = bytes[len];
+
+            // An ESC byte outside of an OSC sequence and an ESC byte inside of
+            // one that is not followed by "\" start a new escape sequence.
+            if byte == 0x1b && stripper.state != State::Osc {
+                break;
+            }
+            if stripper.state == State::OscEscape && byte != b'\\' {
+                len -= 1;
+                break;
+            }
+            if stripper.next(byte) {
+                break;
+            }
+            len += 1;
+
+            if stripper.state == State::Text {
+                break;
+            }
+        }
+
+        // Sequences only end at ASCII bytes or before the first byte of a
+        // character, so `len` is at a character boundary.
+        pos += len;
+        Some(Token::Escape(&rest[..len]))
+    })
+}
+
+/// A writer that removes ANSI escape sequences from the bytes written to it
+/// before writing them to the inner writer.
+///
+/// Escape sequences that are split across several calls to `write` are
+/// removed as well, so the `write_*` macros can write to a log file through
+/// a `StripAnsiWriter` without any other changes.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Write;
+///
+/// use color_macros::{write_styled, StripAnsiWriter};
+///
+/// let mut log = StripAnsiWriter::new(Vec::new());
+///
+/// write_styled!(&mut log, "bold red", "error");
+/// log.write_all(b": \x1b[3").unwrap();
+/// log.write_all(b"6mnot found\x1b[0m").unwrap();
+///
+/// assert_eq!(log.get_ref().as_slice(), b"error: not found");
+/// ```
+#[derive(Debug)]
+pub struct StripAnsiWriter<W: Write> {
+    inner: W,
+    stripper: Stripper,
+}
+
+impl<W: Write> StripAnsiWriter<W> {
+    /// Returns a new `StripAnsiWriter` that writes to `inner`.
+    pub const fn new(inner: W) -> Self {
+        Self { inner, stripper: Stripper::new() }
+    }
+
+    /// Returns a reference to the inner writer.
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    pub const fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the inner writer. The start of an escape sequence that was
+    /// cut off by the last write is discarded.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StripAnsiWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        self.stripper.strip(buf, |run| inner.write_all(run))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The position of a `Stripper` in the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Outside of an escape sequence.
+    Text,
+    /// After an ESC byte.
+    Escape,
+    /// Inside of a CSI sequence ("ESC [").
+    Csi,
+    /// Inside of an OSC sequence ("ESC ]"), which ends with BEL or "ESC \".
+    Osc,
+    /// After an ESC byte inside of an OSC sequence.
+    OscEscape,
+}
+
+/// Removes escape sequences from bytes that are received in chunks.
+#[derive(Debug)]
+pub struct Stripper {
+    state: State,
+}
+
+impl Stripper {
+    /// Returns a new `Stripper` outside of an escape sequence.
+    pub const fn new() -> Self {
+        Self { state: State::Text }
+    }
+
+    /// Passes each run of `bytes` that is not part of an escape sequence to
+    /// `text`, continuing any escape sequence the previous chunk ended in.
+    pub fn strip(
+        &mut self,
+        bytes: &[u8],
+        mut text: impl FnMut(&[u8]) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut start = 0;
+
+        for (idx, &byte) in bytes.iter().enumerate() {
+            if self.state == State::Text {
+                if byte == 0x1b {
+                    if start < idx {
+                        text(&bytes[start..idx])?;
+                    }
+                    self.state = State::Escape;
+                }
+                continue;
+            }
+
+            if self.next(byte) {
+                // The byte does not belong to the escape sequence.
+                start = idx;
+            } else if self.state == State::Text {
+                start = idx + 1;
+            }
+        }
+
+        if self.state == State::Text && start < bytes.len() {
+            text(&bytes[start..])?;
+        }
+        Ok(())
+    }
+
+    /// Advances the state inside of an escape sequence by `byte`, returning
+    /// true if `byte` ends the sequence without being a part of it.
+    const fn next(&mut self, byte: u8) -> bool {
+        self.state = match (self.state, byte) {
+            (State::Escape | State::OscEscape | State::Csi, 0x1b) => State::Escape,
+            (State::Osc, 0x1b) => State::OscEscape,
+            // Parameter and intermediate bytes continue a CSI sequence.
+            (State::Escape | State::OscEscape, b'[') | (State::Csi, 0x20..=0x3f) => State::Csi,
+            (State::Escape | State::OscEscape, b']') => State::Osc,
+            // Other escape sequences are two bytes long (e.g. "ESC 7"), and
+            // "ESC \" ends an OSC sequence.
+            (State::Escape | State::OscEscape, 0x20..=0x7e)
+            | (State::Csi, 0x40..=0x7e)
+            | (State::Osc, 0x07) => State::Text,
+            (State::Osc, _) => State::Osc,
+            // A byte that cannot be part of the sequence cuts it off.
+            _ => {
+                self.state = State::Text;
+                return true;
+            }
+        };
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{parse_ansi, strip_ansi, StripAnsiWriter};
+    use crate::{Attribute, Color, Style};
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("plain"), "plain");
+        assert_eq!(strip_ansi("\x1b[1;38;2;255;0;0mred\x1b[0m text"), "red text");
+        assert_eq!(strip_ansi("\x1b[38:5:196mé\x1b[0m日本"), "é日本");
+        assert_eq!(strip_ansi("a\x1b]0;title\x1b\\b\x1b]8;;url\x07c"), "abc");
+        assert_eq!(strip_ansi("\x1b7\x1b[2Kline\x1b8"), "line");
+        assert_eq!(strip_ansi("cut off \x1b[38;5"), "cut off ");
+        assert_eq!(strip_ansi("\x1b\x1b[1mx"), "x");
+        assert_eq!(strip_ansi("\x1b[1\u{00e9}"), "\u{00e9}");
+    }
+
+    #[test]
+    fn test_strip_ansi_writer_split_sequences() {
+        let input = b"a\x1b[1;31mb\x1b[0m\x1b]0;t\x1b\\c\x1b7d";
+
+        // Write the input in every possible pair of chunks.
+        for split in 0..=input.len() {
+            let mut writer = StripAnsiWriter::new(Vec::new());
+            writer.write_all(&input[..split]).unwrap();
+            writer.write_all(&input[split..]).unwrap();
+            writer.flush().unwrap();
+
+            assert_eq!(writer.into_inner(), b"abcd", "split at {split}");
+        }
+
+        // Write the input one byte at a time.
+        let mut writer = StripAnsiWriter::new(Vec::new());
+        for byte in input {
+            assert_eq!(writer.write(&[*byte]).unwrap(), 1);
+        }
+        assert_eq!(writer.get_ref().as_slice(), b"abcd");
+    }
+
+    #[test]
+    fn test_parse_ansi_round_trip() {
+        let colors = [Color::Red, Color::BrightCyan, Color::Color256(208), Color::Rgb(1, 2, 3)];
+
+        for fg in colors {
+            for bg in colors {
+                for attr in Attribute::ALL {
+                    let style = Style::new().fg(fg).on(bg).attr(attr);
+                    let text = format!("{style}x\x1b[0my");
+
+                    assert_eq!(parse_ansi(&text), [(style, "x"), (Style::new(), "y")], "{text:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_ansi_codes() {
+        let style = |text| parse_ansi(text)[0].0;
+        let rgb = Style::new().fg(Color::Rgb(255, 136, 0));
+
+        assert_eq!(style("\x1b[38:2::255:136:0mx"), rgb);
+        assert_eq!(style("\x1b[38:2:255:136:0mx"), rgb);
+        assert_eq!(style("\x1b[48:5:17mx"), Style::new().on(Color::Color256(17)));
+        assert_eq!(style("\x1b[1;2;3;22mx"), Style::new().italic());
+        assert_eq!(style("\x1b[31;44m\x1b[39mx"), Style::new().on(Color::Blue));
+        assert_eq!(style("\x1b[1;31m\x1b[mx"), Style::new());
+        assert_eq!(style("\x1b[38;5;300;1mx"), Style::new().bold());
+        assert_eq!(style("\x1b[4;58;5;1;95mx"), Style::new().fg(Color::BrightMagenta).underline());
+        assert_eq!(
+            parse_ansi("\x1b[1ma\x1b[2Kb\x1b]0;t\x07c"),
+            [(Style::new().bold(), "a"), (Style::new().bold(), "b"), (Style::new().bold(), "c")]
+        );
+        assert_eq!(parse_ansi(""), []);
+    }
+}
@@ -0,0 +1,226 @@
+//! Text attributes and their ANSI codes.
+
+use std::ops::{BitOr, BitOrAssign};
+
+use crate::parse::ConstStr;
+
+/// Text attribute options
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    /// Bold or increased intensity
+    Bold,
+    /// Dim or decreased intensity
+    Dim,
+    /// Italic
+    Italic,
+    /// Underline
+    Underline,
+    /// Blink
+    Blink,
+    /// Reverse the foreground and background colors
+    Reverse,
+    /// Hidden
+    Hidden,
+    /// Strikethrough
+    Strikethrough,
+}
+
+impl Attribute {
+    /// All attributes in the order of their ANSI codes.
+    pub const ALL: [Self; 8] = [
+        Self::Bold,
+        Self::Dim,
+        Self::Italic,
+        Self::Underline,
+        Self::Blink,
+        Self::Reverse,
+        Self::Hidden,
+        Self::Strikethrough,
+    ];
+
+    /// Returns the name of this attribute as it is written in a style string.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Bold => "bold",
+            Self::Dim => "dim",
+            Self::Italic => "italic",
+            Self::Underline => "underline",
+            Self::Blink => "blink",
+            Self::Reverse => "reverse",
+            Self::Hidden => "hidden",
+            Self::Strikethrough => "strikethrough",
+        }
+    }
+
+    /// Returns the ANSI code of this attribute.
+    #[must_use]
+    pub const fn get_ansi_code(self) -> u8 {
+        match self {
+            Self::Bold => 1,
+            Self::Dim => 2,
+            Self::Italic => 3,
+            Self::Underline => 4,
+            Self::Blink => 5,
+            Self::Reverse => 7,
+            Self::Hidden => 8,
+            Self::Strikethrough => 9,
+        }
+    }
+
+    /// Returns the bit that represents this attribute in `Attributes`.
+    const fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// A set of text attributes.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Attribute, Attributes};
+///
+/// let attrs = Attribute::Bold | Attribute::Underline;
+///
+/// assert!(attrs.contains(Attribute::Bold));
+/// assert!(!attrs.contains(Attribute::Italic));
+/// assert_eq!(attrs.iter().count(), 2);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Attributes(u8);
+
+impl Attributes {
+    /// Returns an empty set of attributes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Returns this set of attributes with `attr` added to it.
+    #[must_use]
+    pub const fn with(self, attr: Attribute) -> Self {
+        Self(self.0 | attr.bit())
+    }
+
+    /// Returns this set of attributes with `attr` removed from it.
+    #[must_use]
+    pub const fn without(self, attr: Attribute) -> Self {
+        Self(self.0 & !attr.bit())
+    }
+
+    /// Returns true if `attr` is in this set of attributes.
+    #[must_use]
+    pub const fn contains(self, attr: Attribute) -> bool {
+        self.0 & attr.bit() != 0
+    }
+
+    /// Returns true if this set of attributes is empty.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the attributes in this set in the order of
+    /// their ANSI codes.
+    pub fn iter(self) -> impl Iterator<Item = Attribute> {
+        Attribute::ALL.into_iter().filter(move |attr| self.contains(*attr))
+    }
+
+    /// Appends the ANSI codes of the attributes in this set to `codes`,
+    /// separated by semicolons.
+    #[must_use]
+    pub(crate) const fn push_ansi_codes<const N: usize>(
+        self,
+        mut codes: ConstStr<N>,
+    ) -> ConstStr<N> {
+        let mut i = 0;
+        while i < Attribute::ALL.len() {
+            let attr = Attribute::ALL[i];
+
+            if self.contains(attr) {
+                if !codes.is_empty() {
+                    codes = codes.push_str(";");
+                }
+                codes = codes.push_u8(attr.get_ansi_code());
+            }
+            i += 1;
+        }
+        codes
+    }
+}
+
+impl From<Attribute> for Attributes {
+    fn from(attr: Attribute) -> Self {
+        Self::new().with(attr)
+    }
+}
+
+impl BitOr for Attribute {
+    type Output = Attributes;
+
+    fn bitor(self, rhs: Self) -> Attributes {
+        Attributes::from(self).with(rhs)
+    }
+}
+
+impl BitOr<Attribute> for Attributes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Attribute) -> Self {
+        self.with(rhs)
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign<Attribute> for Attributes {
+    fn bitor_assign(&mut self, rhs: Attribute) {
+        *self = self.with(rhs);
+    }
+}
+
+impl FromIterator<Attribute> for Attributes {
+    fn from_iter<I: IntoIterator<Item = Attribute>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), Self::with)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Attribute, Attributes};
+    use crate::parse::Codes;
+
+    #[test]
+    fn test_attribute_set_operations() {
+        let mut attrs = Attributes::new();
+        assert!(attrs.is_empty());
+
+        attrs |= Attribute::Strikethrough;
+        attrs |= Attribute::Bold;
+        assert!(attrs.contains(Attribute::Bold));
+        assert!(attrs.contains(Attribute::Strikethrough));
+        assert!(!attrs.contains(Attribute::Dim));
+
+        let attrs = attrs.without(Attribute::Bold);
+        assert_eq!(attrs.iter().collect::<Vec<_>>(), [Attribute::Strikethrough]);
+
+        let all: Attributes = Attribute::ALL.into_iter().collect();
+        assert_eq!(all.iter().count(), 8);
+    }
+
+    #[test]
+    fn test_attribute_ansi_codes() {
+        let all: Attributes = Attribute::ALL.into_iter().collect();
+        assert_eq!(all.push_ansi_codes(Codes::new()).as_str(), "1;2;3;4;5;7;8;9");
+
+        let attrs = Attribute::Underline | Attribute::Bold;
+        assert_eq!(attrs.push_ansi_codes(Codes::new()).as_str(), "1;4");
+    }
+}
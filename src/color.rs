@@ -0,0 +1,740 @@
+//! Colors and their ANSI color codes.
+
+use std::str::FromStr;
+
+use crate::control::{self, ColorDepth};
+use crate::parse::{self, ConstStr};
+use crate::style::ParseStyleError;
+
+/// The RGB values of the 8 basic and 8 bright colors in xterm's default
+/// palette, in the order of their 256-color mode color numbers.
+const XTERM_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The values of each RGB component in the 6x6x6 color cube of the
+/// 256-color mode colors 16 - 231.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Color options
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// Black
+    Black,
+    /// Red
+    Red,
+    /// Green
+    Green,
+    /// Yellow
+    Yellow,
+    /// Blue
+    Blue,
+    /// Magenta
+    Magenta,
+    /// Cyan
+    Cyan,
+    /// White
+    White,
+    /// Bright black
+    BrightBlack,
+    /// Bright red
+    BrightRed,
+    /// Bright green
+    BrightGreen,
+    /// Bright yellow
+    BrightYellow,
+    /// Bright blue
+    BrightBlue,
+    /// Bright magenta
+    BrightMagenta,
+    /// Bright cyan
+    BrightCyan,
+    /// Bright white
+    BrightWhite,
+    /// 256-color mode colors
+    Color256(u8),
+    /// 24-bit RGB colors
+    Rgb(u8, u8, u8),
+    /// Current color value
+    Current,
+    /// Reset color attributes
+    Reset,
+}
+
+impl Color {
+    /// The basic colors in the order of their ANSI color codes.
+    pub(crate) const BASIC: [Self; 8] = [
+        Self::Black,
+        Self::Red,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Magenta,
+        Self::Cyan,
+        Self::White,
+    ];
+
+    /// The bright colors in the order of their ANSI color codes.
+    pub(crate) const BRIGHT: [Self; 8] = [
+        Self::BrightBlack,
+        Self::BrightRed,
+        Self::BrightGreen,
+        Self::BrightYellow,
+        Self::BrightBlue,
+        Self::BrightMagenta,
+        Self::BrightCyan,
+        Self::BrightWhite,
+    ];
+
+    /// Returns true if this `Color` instance is `Color::Current`.
+    #[must_use]
+    pub const fn is_current(&self) -> bool {
+        matches!(self, Self::Current)
+    }
+
+    /// Returns a semicolon separator if self and other are not `Current`.
+    /// Else it returns an empty string.
+    #[must_use]
+    pub fn get_separator(&self, other_is_current: bool) -> String {
+        if !self.is_current() && !other_is_current {
+            ";".to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    /// Returns the bright version of a basic color. Any other color is
+    /// returned unchanged.
+    #[must_use]
+    pub const fn to_bright(self) -> Self {
+        match self.basic_index() {
+            Some(idx) => Self::BRIGHT[idx],
+            None => self,
+        }
+    }
+
+    /// Returns the offset of a basic color's ANSI color code from that of
+    /// black (e.g. 1 for red).
+    const fn basic_index(self) -> Option<usize> {
+        match self {
+            Self::Black => Some(0),
+            Self::Red => Some(1),
+            Self::Green => Some(2),
+            Self::Yellow => Some(3),
+            Self::Blue => Some(4),
+            Self::Magenta => Some(5),
+            Self::Cyan => Some(6),
+            Self::White => Some(7),
+            _ => None,
+        }
+    }
+
+    /// Returns the offset of a bright color's ANSI color code from that of
+    /// bright black (e.g. 1 for bright red).
+    const fn bright_index(self) -> Option<usize> {
+        match self {
+            Self::BrightBlack => Some(0),
+            Self::BrightRed => Some(1),
+            Self::BrightGreen => Some(2),
+            Self::BrightYellow => Some(3),
+            Self::BrightBlue => Some(4),
+            Self::BrightMagenta => Some(5),
+            Self::BrightCyan => Some(6),
+            Self::BrightWhite => Some(7),
+            _ => None,
+        }
+    }
+
+    /// Returns the closest color to this one that a terminal with the color
+    /// depth `depth` supports.
+    ///
+    /// RGB colors are downgraded to 256-color mode colors or to the basic and
+    /// bright colors, and 256-color mode colors to the basic and bright
+    /// colors. With `ColorDepth::None` every color except `Reset` becomes
+    /// `Current`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::{Color, ColorDepth};
+    ///
+    /// assert_eq!(Color::Rgb(255, 0, 0).to_depth(ColorDepth::Ansi256), Color::Color256(196));
+    /// assert_eq!(Color::Color256(196).to_depth(ColorDepth::Ansi16), Color::BrightRed);
+    /// assert_eq!(Color::Red.to_depth(ColorDepth::None), Color::Current);
+    /// ```
+    #[must_use]
+    pub const fn to_depth(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (Self::Current | Self::Reset, _) => self,
+            (_, ColorDepth::None) => Self::Current,
+            (Self::Rgb(..), ColorDepth::Ansi256) => self.to_color256(),
+            (_, ColorDepth::Ansi16) => self.to_ansi16(),
+            _ => self,
+        }
+    }
+
+    /// Converts this color into a 24-bit RGB color using xterm's default
+    /// palette. `Current` and `Reset` are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::Color256(208).to_rgb(), Color::Rgb(255, 135, 0));
+    /// assert_eq!(Color::Color256(244).to_rgb(), Color::Rgb(128, 128, 128));
+    /// assert_eq!(Color::Red.to_rgb(), Color::Rgb(205, 0, 0));
+    /// ```
+    #[must_use]
+    pub const fn to_rgb(self) -> Self {
+        let (r, g, b) = match self {
+            Self::Rgb(..) => return self,
+            Self::Color256(num) => rgb_of_256(num),
+            _ => match self.ansi16_num() {
+                Some(num) => XTERM_16[num as usize],
+                None => return self,
+            },
+        };
+        Self::Rgb(r, g, b)
+    }
+
+    /// Converts this color into the closest 256-color mode color. An RGB
+    /// color becomes the perceptually closer of the closest color in the
+    /// 6x6x6 color cube (16 - 231) and the closest step of the grayscale ramp
+    /// (232 - 255). A basic or bright color becomes its equivalent number
+    /// (0 - 15). `Current` and `Reset` are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::Rgb(255, 136, 0).to_color256(), Color::Color256(208));
+    /// assert_eq!(Color::Rgb(100, 100, 104).to_color256(), Color::Color256(241));
+    /// assert_eq!(Color::BrightRed.to_color256(), Color::Color256(9));
+    /// ```
+    #[must_use]
+    pub const fn to_color256(self) -> Self {
+        match self {
+            Self::Rgb(r, g, b) => Self::Color256(rgb_to_256(r, g, b)),
+            _ => match self.ansi16_num() {
+                Some(num) => Self::Color256(num),
+                None => self,
+            },
+        }
+    }
+
+    /// Converts this color into the perceptually closest basic or bright
+    /// color in xterm's default palette. 256-color mode colors 0 - 15 become
+    /// their equivalent colors. `Current` and `Reset` are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::Color256(9).to_ansi16(), Color::BrightRed);
+    /// assert_eq!(Color::Color256(28).to_ansi16(), Color::Green);
+    /// assert_eq!(Color::Rgb(0, 180, 0).to_ansi16(), Color::Green);
+    /// ```
+    #[must_use]
+    pub const fn to_ansi16(self) -> Self {
+        match self {
+            Self::Color256(num) if num < 16 => from_16(num),
+            Self::Color256(num) => {
+                let (r, g, b) = rgb_of_256(num);
+                from_16(nearest_16(r, g, b))
+            }
+            Self::Rgb(r, g, b) => from_16(nearest_16(r, g, b)),
+            _ => self,
+        }
+    }
+
+    /// Returns the 256-color mode color number (0 - 15) of a basic or bright
+    /// color.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn ansi16_num(self) -> Option<u8> {
+        match (self.basic_index(), self.bright_index()) {
+            (Some(idx), _) => Some(idx as u8),
+            (_, Some(idx)) => Some(idx as u8 + 8),
+            _ => None,
+        }
+    }
+
+    /// Appends the numeric portion of this color's foreground or background
+    /// ANSI color code to `codes`. Nothing is appended for `Current`.
+    #[must_use]
+    pub(crate) const fn push_ansi_code<const N: usize>(
+        self,
+        codes: ConstStr<N>,
+        background: bool,
+    ) -> ConstStr<N> {
+        let base: u8 = if background { 40 } else { 30 };
+
+        if let Some(idx) = self.basic_index() {
+            #[allow(clippy::cast_possible_truncation)]
+            return codes.push_u8(base + idx as u8);
+        }
+
+        if let Some(idx) = self.bright_index() {
+            #[allow(clippy::cast_possible_truncation)]
+            return codes.push_u8(base + 60 + idx as u8);
+        }
+
+        let extended = if background { "48;" } else { "38;" };
+
+        match self {
+            Self::Color256(num) => codes.push_str(extended).push_str("5;").push_u8(num),
+            Self::Rgb(r, g, b) => codes
+                .push_str(extended)
+                .push_str("2;")
+                .push_u8(r)
+                .push_str(";")
+                .push_u8(g)
+                .push_str(";")
+                .push_u8(b),
+            Self::Reset => codes.push_str("0"),
+            _ => codes,
+        }
+    }
+
+    /// Returns the numeric portion of this color's ANSI color code.
+    fn ansi_code(self, background: bool) -> String {
+        self.push_ansi_code(parse::Codes::new(), background)
+            .as_str()
+            .to_string()
+    }
+}
+
+impl From<&str> for Color {
+    /// Converts a color name (e.g. "red" or "bright red") into a `Color`.
+    ///
+    /// Unknown color names are converted into `Color::Current`. Use
+    /// `str::parse` to detect them instead.
+    fn from(value: &str) -> Self {
+        value.parse().unwrap_or(Self::Current)
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseStyleError;
+
+    /// Parses a color name (e.g. "red", "Bright Red", "current" or "reset").
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseStyleError` naming the offending token if `s` is not a
+    /// color name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::parse_color(s).map_err(|e| ParseStyleError::new(s, e))
+    }
+}
+
+/// Returns the basic or bright color with the 256-color mode color number
+/// `num` (0 - 15).
+const fn from_16(num: u8) -> Color {
+    if num < 8 {
+        Color::BASIC[num as usize]
+    } else {
+        Color::BRIGHT[(num - 8) as usize]
+    }
+}
+
+/// Returns the RGB value of the 256-color mode color `num` in xterm's
+/// default palette.
+const fn rgb_of_256(num: u8) -> (u8, u8, u8) {
+    match num {
+        0..=15 => XTERM_16[num as usize],
+        16..=231 => {
+            let idx = num - 16;
+            (
+                CUBE_LEVELS[(idx / 36) as usize],
+                CUBE_LEVELS[(idx / 6 % 6) as usize],
+                CUBE_LEVELS[(idx % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (num - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// Returns the index of the color cube level closest to an RGB component.
+const fn cube_index(value: u8) -> u8 {
+    if value < 48 {
+        0
+    } else if value < 115 {
+        1
+    } else {
+        (value - 35) / 40
+    }
+}
+
+/// Returns the perceptual distance between two RGB values, which is the
+/// squared Euclidean distance with each component weighted by the average
+/// amount of red ("redmean"), a cheap approximation of how different the
+/// colors look.
+const fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let red_mean = r1.midpoint(r2) as u32;
+    let dr = r1.abs_diff(r2) as u32;
+    let dg = g1.abs_diff(g2) as u32;
+    let db = b1.abs_diff(b2) as u32;
+
+    (((512 + red_mean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - red_mean) * db * db) >> 8)
+}
+
+/// Returns the 256-color mode color number closest to an RGB value, which is
+/// either in the 6x6x6 color cube or on the grayscale ramp, whichever is
+/// perceptually closer.
+const fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let (qr, qg, qb) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = 16 + 36 * qr + 6 * qg + qb;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray_idx = if average > 238 { 23 } else { average.saturating_sub(3) / 10 };
+    let gray = 8 + 10 * gray_idx;
+
+    if distance((gray, gray, gray), (r, g, b)) < distance(rgb_of_256(cube), (r, g, b)) {
+        232 + gray_idx
+    } else {
+        cube
+    }
+}
+
+/// Returns the number (0 - 15) of the basic or bright color closest to an RGB
+/// value.
+const fn nearest_16(r: u8, g: u8, b: u8) -> u8 {
+    let mut best = 0;
+    let mut num = 1;
+
+    while num < 16 {
+        if distance(XTERM_16[num as usize], (r, g, b))
+            < distance(XTERM_16[best as usize], (r, g, b))
+        {
+            best = num;
+        }
+        num += 1;
+    }
+    best
+}
+
+/// Wrapper that represents a regular foreground color.
+pub struct Fg;
+
+impl Fg {
+    /// Returns the numeric portion of the foreground ANSI color code.
+    #[must_use]
+    pub fn get_ansi_code(color: &Color) -> String {
+        color.to_depth(control::color_depth()).ansi_code(false)
+    }
+}
+
+/// Wrapper that represents a bright foreground color.
+pub struct FgBright;
+
+impl FgBright {
+    /// Returns the numeric portion of the foreground ANSI color code.
+    #[must_use]
+    pub fn get_ansi_code(color: &Color) -> String {
+        color.to_bright().to_depth(control::color_depth()).ansi_code(false)
+    }
+}
+
+/// Wrapper that represents a regular background color.
+pub struct Bg;
+
+impl Bg {
+    /// Returns the numeric portion of the background ANSI color code.
+    #[must_use]
+    pub fn get_ansi_code(color: &Color) -> String {
+        color.to_depth(control::color_depth()).ansi_code(true)
+    }
+}
+
+/// Wrapper that represents a bright background color.
+pub struct BgBright;
+
+impl BgBright {
+    /// Returns the numeric portion of the foreground ANSI color code.
+    #[must_use]
+    pub fn get_ansi_code(color: &Color) -> String {
+        color.to_bright().to_depth(control::color_depth()).ansi_code(true)
+    }
+}
+
+/// The RGB values that a renderer such as `Html` displays the basic and
+/// bright colors and the default colors with.
+///
+/// Terminals let their users choose these colors, so a rendering can only
+/// look like a particular terminal with its palette. `Palette::default()`
+/// is `Palette::XTERM`, which `Color::to_rgb` uses as well. The 256-color
+/// mode colors 16 - 255 and RGB colors are not part of a palette.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Color, Palette};
+///
+/// let palette = Palette::default()
+///     .with(Color::Red, (224, 108, 117))
+///     .with_background((40, 44, 52));
+///
+/// assert_eq!(palette.rgb(Color::Red), Some((224, 108, 117)));
+/// assert_eq!(palette.rgb(Color::Color256(1)), Some((224, 108, 117)));
+/// assert_eq!(palette.rgb(Color::Color256(208)), Some((255, 135, 0)));
+/// assert_eq!(palette.background(), (40, 44, 52));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Palette {
+    colors: [(u8, u8, u8); 16],
+    foreground: (u8, u8, u8),
+    background: (u8, u8, u8),
+}
+
+impl Palette {
+    /// xterm's default palette, with light gray text on black.
+    pub const XTERM: Self = Self::new(XTERM_16);
+
+    /// Returns a palette of the 16 basic and bright colors, in the order of
+    /// their 256-color mode color numbers. The default colors are white
+    /// text on black.
+    #[must_use]
+    pub const fn new(colors: [(u8, u8, u8); 16]) -> Self {
+        Self { colors, foreground: colors[7], background: colors[0] }
+    }
+
+    /// Returns this palette with the basic or bright color `color` (or the
+    /// 256-color mode color 0 - 15) set to `rgb`. Other colors are ignored.
+    #[must_use]
+    pub const fn with(mut self, color: Color, rgb: (u8, u8, u8)) -> Self {
+        if let Some(num) = palette_num(color) {
+            self.colors[num as usize] = rgb;
+        }
+        self
+    }
+
+    /// Returns this palette with its default text color set to `rgb`.
+    #[must_use]
+    pub const fn with_foreground(mut self, rgb: (u8, u8, u8)) -> Self {
+        self.foreground = rgb;
+        self
+    }
+
+    /// Returns this palette with its default background color set to `rgb`.
+    #[must_use]
+    pub const fn with_background(mut self, rgb: (u8, u8, u8)) -> Self {
+        self.background = rgb;
+        self
+    }
+
+    /// Returns the default text color.
+    #[must_use]
+    pub const fn foreground(&self) -> (u8, u8, u8) {
+        self.foreground
+    }
+
+    /// Returns the default background color.
+    #[must_use]
+    pub const fn background(&self) -> (u8, u8, u8) {
+        self.background
+    }
+
+    /// Returns the RGB value that `color` is displayed with, or `None` for
+    /// `Current` and `Reset`.
+    #[must_use]
+    pub const fn rgb(&self, color: Color) -> Option<(u8, u8, u8)> {
+        if let Some(num) = palette_num(color) {
+            return Some(self.colors[num as usize]);
+        }
+        match color.to_rgb() {
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::XTERM
+    }
+}
+
+/// Returns the number (0 - 15) of a basic or bright color or of one of the
+/// first 16 256-color mode colors.
+pub const fn palette_num(color: Color) -> Option<u8> {
+    match color {
+        Color::Color256(num) if num < 16 => Some(num),
+        _ => color.ansi16_num(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Color;
+    use crate::ColorDepth;
+
+    /// The reference RGB values of the 256 colors in xterm's default palette.
+    const XTERM_256: [(u8, u8, u8); 256] = [
+        (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205),
+        (0, 205, 205), (229, 229, 229), (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+        (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255), (0, 0, 0), (0, 0, 95),
+        (0, 0, 135), (0, 0, 175), (0, 0, 215), (0, 0, 255), (0, 95, 0), (0, 95, 95),
+        (0, 95, 135), (0, 95, 175), (0, 95, 215), (0, 95, 255), (0, 135, 0), (0, 135, 95),
+        (0, 135, 135), (0, 135, 175), (0, 135, 215), (0, 135, 255), (0, 175, 0), (0, 175, 95),
+        (0, 175, 135), (0, 175, 175), (0, 175, 215), (0, 175, 255), (0, 215, 0), (0, 215, 95),
+        (0, 215, 135), (0, 215, 175), (0, 215, 215), (0, 215, 255), (0, 255, 0), (0, 255, 95),
+        (0, 255, 135), (0, 255, 175), (0, 255, 215), (0, 255, 255), (95, 0, 0), (95, 0, 95),
+        (95, 0, 135), (95, 0, 175), (95, 0, 215), (95, 0, 255), (95, 95, 0), (95, 95, 95),
+        (95, 95, 135), (95, 95, 175), (95, 95, 215), (95, 95, 255), (95, 135, 0), (95, 135, 95),
+        (95, 135, 135), (95, 135, 175), (95, 135, 215), (95, 135, 255), (95, 175, 0), (95, 175, 95),
+        (95, 175, 135), (95, 175, 175), (95, 175, 215), (95, 175, 255), (95, 215, 0), (95, 215, 95),
+        (95, 215, 135), (95, 215, 175), (95, 215, 215), (95, 215, 255), (95, 255, 0), (95, 255, 95),
+        (95, 255, 135), (95, 255, 175), (95, 255, 215), (95, 255, 255), (135, 0, 0), (135, 0, 95),
+        (135, 0, 135), (135, 0, 175), (135, 0, 215), (135, 0, 255), (135, 95, 0), (135, 95, 95),
+        (135, 95, 135), (135, 95, 175), (135, 95, 215), (135, 95, 255), (135, 135, 0), (135, 135, 95),
+        (135, 135, 135), (135, 135, 175), (135, 135, 215), (135, 135, 255), (135, 175, 0), (135, 175, 95),
+        (135, 175, 135), (135, 175, 175), (135, 175, 215), (135, 175, 255), (135, 215, 0), (135, 215, 95),
+        (135, 215, 135), (135, 215, 175), (135, 215, 215), (135, 215, 255), (135, 255, 0), (135, 255, 95),
+        (135, 255, 135), (135, 255, 175), (135, 255, 215), (135, 255, 255), (175, 0, 0), (175, 0, 95),
+        (175, 0, 135), (175, 0, 175), (175, 0, 215), (175, 0, 255), (175, 95, 0), (175, 95, 95),
+        (175, 95, 135), (175, 95, 175), (175, 95, 215), (175, 95, 255), (175, 135, 0), (175, 135, 95),
+        (175, 135, 135), (175, 135, 175), (175, 135, 215), (175, 135, 255), (175, 175, 0), (175, 175, 95),
+        (175, 175, 135), (175, 175, 175), (175, 175, 215), (175, 175, 255), (175, 215, 0), (175, 215, 95),
+        (175, 215, 135), (175, 215, 175), (175, 215, 215), (175, 215, 255), (175, 255, 0), (175, 255, 95),
+        (175, 255, 135), (175, 255, 175), (175, 255, 215), (175, 255, 255), (215, 0, 0), (215, 0, 95),
+        (215, 0, 135), (215, 0, 175), (215, 0, 215), (215, 0, 255), (215, 95, 0), (215, 95, 95),
+        (215, 95, 135), (215, 95, 175), (215, 95, 215), (215, 95, 255), (215, 135, 0), (215, 135, 95),
+        (215, 135, 135), (215, 135, 175), (215, 135, 215), (215, 135, 255), (215, 175, 0), (215, 175, 95),
+        (215, 175, 135), (215, 175, 175), (215, 175, 215), (215, 175, 255), (215, 215, 0), (215, 215, 95),
+        (215, 215, 135), (215, 215, 175), (215, 215, 215), (215, 215, 255), (215, 255, 0), (215, 255, 95),
+        (215, 255, 135), (215, 255, 175), (215, 255, 215), (215, 255, 255), (255, 0, 0), (255, 0, 95),
+        (255, 0, 135), (255, 0, 175), (255, 0, 215), (255, 0, 255), (255, 95, 0), (255, 95, 95),
+        (255, 95, 135), (255, 95, 175), (255, 95, 215), (255, 95, 255), (255, 135, 0), (255, 135, 95),
+        (255, 135, 135), (255, 135, 175), (255, 135, 215), (255, 135, 255), (255, 175, 0), (255, 175, 95),
+        (255, 175, 135), (255, 175, 175), (255, 175, 215), (255, 175, 255), (255, 215, 0), (255, 215, 95),
+        (255, 215, 135), (255, 215, 175), (255, 215, 215), (255, 215, 255), (255, 255, 0), (255, 255, 95),
+        (255, 255, 135), (255, 255, 175), (255, 255, 215), (255, 255, 255), (8, 8, 8), (18, 18, 18),
+        (28, 28, 28), (38, 38, 38), (48, 48, 48), (58, 58, 58), (68, 68, 68), (78, 78, 78),
+        (88, 88, 88), (98, 98, 98), (108, 108, 108), (118, 118, 118), (128, 128, 128), (138, 138, 138),
+        (148, 148, 148), (158, 158, 158), (168, 168, 168), (178, 178, 178), (188, 188, 188), (198, 198, 198),
+        (208, 208, 208), (218, 218, 218), (228, 228, 228), (238, 238, 238),
+    ];
+
+    #[test]
+    fn test_ansi_codes() {
+        // The `get_ansi_code` functions downgrade to the process-wide color
+        // depth, which other tests may rely on, so use an explicit one.
+        let fg = |color: Color| color.to_depth(ColorDepth::TrueColor).ansi_code(false);
+        let bg = |color: Color| color.to_depth(ColorDepth::TrueColor).ansi_code(true);
+
+        assert_eq!(fg(Color::Red), "31");
+        assert_eq!(fg(Color::BrightRed), "91");
+        assert_eq!(fg(Color::Red.to_bright()), "91");
+        assert_eq!(bg(Color::White), "47");
+        assert_eq!(bg(Color::BrightWhite), "107");
+        assert_eq!(bg(Color::White.to_bright()), "107");
+        assert_eq!(fg(Color::Color256(7)), "38;5;7");
+        assert_eq!(bg(Color::Rgb(0, 10, 255)), "48;2;0;10;255");
+        assert_eq!(fg(Color::Reset.to_bright()), "0");
+        assert_eq!(bg(Color::Current.to_bright()), "");
+        assert_eq!(bg(Color::Rgb(255, 0, 0).to_depth(ColorDepth::Ansi256)), "48;5;196");
+    }
+
+    #[test]
+    fn test_color_from_str() {
+        assert_eq!("red".parse::<Color>().unwrap(), Color::Red);
+        assert_eq!("Bright  BLUE".parse::<Color>().unwrap(), Color::BrightBlue);
+        assert_eq!("current".parse::<Color>().unwrap(), Color::Current);
+        assert_eq!("reset".parse::<Color>().unwrap(), Color::Reset);
+
+        let err = "bright purpel".parse::<Color>().unwrap_err();
+        assert_eq!(err.token(), "purpel");
+        assert_eq!(err.position(), 7);
+        assert_eq!(err.suggestion(), Some("magenta"));
+
+        assert!("red on blue".parse::<Color>().is_err());
+        assert_eq!(Color::from("purpel"), Color::Current);
+    }
+
+    #[test]
+    fn test_to_depth() {
+        let rgb = Color::Rgb(95, 135, 175);
+        assert_eq!(rgb.to_depth(ColorDepth::TrueColor), rgb);
+        assert_eq!(rgb.to_depth(ColorDepth::Ansi256), Color::Color256(67));
+        assert_eq!(Color::Rgb(128, 128, 128).to_depth(ColorDepth::Ansi256), Color::Color256(244));
+        assert_eq!(Color::Rgb(0, 0, 0).to_depth(ColorDepth::Ansi256), Color::Color256(16));
+        assert_eq!(Color::Rgb(250, 10, 20).to_depth(ColorDepth::Ansi16), Color::BrightRed);
+        assert_eq!(Color::Rgb(0, 0, 200).to_depth(ColorDepth::Ansi16), Color::Blue);
+
+        assert_eq!(Color::Color256(9).to_depth(ColorDepth::Ansi16), Color::BrightRed);
+        assert_eq!(Color::Color256(2).to_depth(ColorDepth::Ansi16), Color::Green);
+        assert_eq!(Color::Color256(232).to_depth(ColorDepth::Ansi16), Color::Black);
+        assert_eq!(Color::Color256(196).to_depth(ColorDepth::Ansi256), Color::Color256(196));
+
+        assert_eq!(Color::Green.to_depth(ColorDepth::Ansi16), Color::Green);
+        assert_eq!(Color::Color256(7).to_depth(ColorDepth::None), Color::Current);
+        assert_eq!(Color::Reset.to_depth(ColorDepth::None), Color::Reset);
+    }
+
+    #[test]
+    fn test_color256_to_rgb_matches_xterm() {
+        for (num, &(r, g, b)) in (0..=255).zip(XTERM_256.iter()) {
+            assert_eq!(Color::Color256(num).to_rgb(), Color::Rgb(r, g, b), "color {num}");
+        }
+        assert_eq!(Color::BrightBlue.to_rgb(), Color::Rgb(92, 92, 255));
+        assert_eq!(Color::Rgb(1, 2, 3).to_rgb(), Color::Rgb(1, 2, 3));
+        assert_eq!(Color::Current.to_rgb(), Color::Current);
+    }
+
+    #[test]
+    fn test_rgb_to_color256() {
+        // Every color of the cube and the grayscale ramp maps back to itself.
+        for (num, &(r, g, b)) in (0..=255).zip(XTERM_256.iter()).skip(16) {
+            assert_eq!(Color::Rgb(r, g, b).to_color256(), Color::Color256(num), "color {num}");
+        }
+
+        assert_eq!(Color::Rgb(255, 136, 0).to_color256(), Color::Color256(208));
+        assert_eq!(Color::Rgb(100, 100, 104).to_color256(), Color::Color256(241));
+        assert_eq!(Color::Rgb(250, 250, 250).to_color256(), Color::Color256(231));
+        assert_eq!(Color::Rgb(3, 3, 3).to_color256(), Color::Color256(16));
+        assert_eq!(Color::Magenta.to_color256(), Color::Color256(5));
+        assert_eq!(Color::Reset.to_color256(), Color::Reset);
+    }
+
+    #[test]
+    fn test_to_ansi16() {
+        let all_16: Vec<Color> = Color::BASIC.into_iter().chain(Color::BRIGHT).collect();
+
+        for (num, &color) in (0..16).zip(all_16.iter()) {
+            assert_eq!(Color::Color256(num).to_ansi16(), color);
+            assert_eq!(color.to_rgb().to_ansi16(), color);
+            assert_eq!(color.to_color256(), Color::Color256(num));
+        }
+
+        let corners = [(16, Color::Black), (21, Color::Blue), (46, Color::BrightGreen)]
+            .into_iter()
+            .chain([(51, Color::BrightCyan), (196, Color::BrightRed)])
+            .chain([(201, Color::BrightMagenta), (226, Color::BrightYellow)])
+            .chain([(231, Color::BrightWhite), (244, Color::BrightBlack)]);
+        for (num, color) in corners {
+            assert_eq!(Color::Color256(num).to_ansi16(), color, "color {num}");
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Conversions between `Color::Rgb` and the HSL, HSV, CIE Lab and Oklab
+//! color spaces.
+
+// The conventional single-letter names of the color components are clearer
+// than longer ones in the formulas below.
+#![allow(clippy::many_single_char_names)]
+
+use crate::Color;
+
+/// The CIE XYZ values of the D65 reference white.
+const WHITE_X: f64 = 0.950_47;
+const WHITE_Y: f64 = 1.0;
+const WHITE_Z: f64 = 1.088_83;
+
+/// The constants of the CIE Lab transfer function.
+const LAB_EPSILON: f64 = 216.0 / 24_389.0;
+const LAB_KAPPA: f64 = 24_389.0 / 27.0;
+
+impl Color {
+    /// Returns the RGB color with the hue `h` (in degrees), saturation `s`
+    /// and lightness `l` (both from 0.0 to 1.0).
+    ///
+    /// Hues outside of 0 - 360 wrap around and the other components are
+    /// clamped to their range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// // Orange
+    /// assert_eq!(Color::from_hsl(30.0, 1.0, 0.5), Color::Rgb(255, 128, 0));
+    /// ```
+    #[must_use]
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+        let chroma = (1.0 - 2.0f64.mul_add(l, -1.0).abs()) * s;
+
+        from_hue_chroma(h, chroma, l - chroma / 2.0)
+    }
+
+    /// Returns the RGB color with the hue `h` (in degrees), saturation `s`
+    /// and value `v` (both from 0.0 to 1.0).
+    ///
+    /// Hues outside of 0 - 360 wrap around and the other components are
+    /// clamped to their range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::from_hsv(240.0, 0.5, 1.0), Color::Rgb(128, 128, 255));
+    /// ```
+    #[must_use]
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let chroma = v * s;
+
+        from_hue_chroma(h, chroma, v - chroma)
+    }
+
+    /// Returns the RGB color closest to the CIE Lab color with the lightness
+    /// `l` (from 0.0 to 100.0) and the green-red and blue-yellow components
+    /// `a` and `b`, using the D65 reference white.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::from_lab(53.24, 80.09, 67.2), Color::Rgb(255, 0, 0));
+    /// ```
+    #[must_use]
+    pub fn from_lab(l: f64, a: f64, b: f64) -> Self {
+        let fy = (l + 16.0) / 116.0;
+        let fx = fy + a / 500.0;
+        let fz = fy - b / 200.0;
+
+        let x = WHITE_X * lab_f_inverse(fx);
+        let y = WHITE_Y * lab_f_inverse(fy);
+        let z = WHITE_Z * lab_f_inverse(fz);
+
+        from_linear(
+            3.240_454_2f64.mul_add(x, (-1.537_138_5f64).mul_add(y, -0.498_531_4 * z)),
+            (-0.969_266_0f64).mul_add(x, 1.876_010_8f64.mul_add(y, 0.041_556_0 * z)),
+            0.055_643_4f64.mul_add(x, (-0.204_025_9f64).mul_add(y, 1.057_225_2 * z)),
+        )
+    }
+
+    /// Returns the RGB color closest to the Oklab color with the lightness
+    /// `l` (from 0.0 to 1.0) and the green-red and blue-yellow components `a`
+    /// and `b`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::from_oklab(0.628, 0.2249, 0.1258), Color::Rgb(255, 0, 0));
+    /// ```
+    #[must_use]
+    pub fn from_oklab(l: f64, a: f64, b: f64) -> Self {
+        let l_ = 0.396_337_777_4f64.mul_add(a, 0.215_803_757_3f64.mul_add(b, l));
+        let m_ = (-0.105_561_345_8f64).mul_add(a, (-0.063_854_172_8f64).mul_add(b, l));
+        let s_ = (-0.089_484_177_5f64).mul_add(a, (-1.291_485_548_0f64).mul_add(b, l));
+
+        let (l, m, s) = (l_.powi(3), m_.powi(3), s_.powi(3));
+
+        from_linear(
+            4.076_741_662_1f64.mul_add(l, (-3.307_711_591_3f64).mul_add(m, 0.230_969_929_2 * s)),
+            (-1.268_438_004_6f64).mul_add(l, 2.609_757_401_1f64.mul_add(m, -0.341_319_396_5 * s)),
+            (-0.004_196_086_3f64).mul_add(l, (-0.703_418_614_7f64).mul_add(m, 1.707_614_701_0 * s)),
+        )
+    }
+
+    /// Returns the hue (in degrees from 0.0 to 360.0), saturation and
+    /// lightness (both from 0.0 to 1.0) of this color.
+    ///
+    /// Basic, bright and 256-color mode colors are converted using xterm's
+    /// default palette (see `to_rgb`). Returns `None` for `Current` and
+    /// `Reset`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::Rgb(255, 0, 0).to_hsl(), Some((0.0, 1.0, 0.5)));
+    /// assert_eq!(Color::Current.to_hsl(), None);
+    /// ```
+    #[must_use]
+    pub fn to_hsl(self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.rgb_components()?;
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let chroma = max - min;
+        let l = max.midpoint(min);
+        let s = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - 2.0f64.mul_add(l, -1.0).abs())
+        };
+
+        Some((hue(r, g, b), s, l))
+    }
+
+    /// Returns the hue (in degrees from 0.0 to 360.0), saturation and value
+    /// (both from 0.0 to 1.0) of this color.
+    ///
+    /// Basic, bright and 256-color mode colors are converted using xterm's
+    /// default palette (see `to_rgb`). Returns `None` for `Current` and
+    /// `Reset`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::Color;
+    ///
+    /// assert_eq!(Color::Rgb(0, 0, 255).to_hsv(), Some((240.0, 1.0, 1.0)));
+    /// ```
+    #[must_use]
+    pub fn to_hsv(self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.rgb_components()?;
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        let s = if max == 0.0 { 0.0 } else { chroma / max };
+
+        Some((hue(r, g, b), s, max))
+    }
+
+    /// Returns the CIE Lab lightness (from 0.0 to 100.0) and green-red and
+    /// blue-yellow components of this color, using the D65 reference white.
+    ///
+    /// Basic, bright and 256-color mode colors are converted using xterm's
+    /// default palette (see `to_rgb`). Returns `None` for `Current` and
+    /// `Reset`.
+    #[must_use]
+    pub fn to_lab(self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.linear_components()?;
+
+        let x = 0.412_456_4f64.mul_add(r, 0.357_576_1f64.mul_add(g, 0.180_437_5 * b));
+        let y = 0.212_672_9f64.mul_add(r, 0.715_152_2f64.mul_add(g, 0.072_175_0 * b));
+        let z = 0.019_333_9f64.mul_add(r, 0.119_192_0f64.mul_add(g, 0.950_304_1 * b));
+
+        let fx = lab_f(x / WHITE_X);
+        let fy = lab_f(y / WHITE_Y);
+        let fz = lab_f(z / WHITE_Z);
+
+        Some((116.0f64.mul_add(fy, -16.0), 500.0 * (fx - fy), 200.0 * (fy - fz)))
+    }
+
+    /// Returns the Oklab lightness (from 0.0 to 1.0) and green-red and
+    /// blue-yellow components of this color.
+    ///
+    /// Basic, bright and 256-color mode colors are converted using xterm's
+    /// default palette (see `to_rgb`). Returns `None` for `Current` and
+    /// `Reset`.
+    #[must_use]
+    pub fn to_oklab(self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.linear_components()?;
+
+        let l = 0.412_221_470_8f64.mul_add(r, 0.536_332_536_3f64.mul_add(g, 0.051_445_992_9 * b));
+        let m = 0.211_903_498_2f64.mul_add(r, 0.680_699_545_1f64.mul_add(g, 0.107_396_956_6 * b));
+        let s = 0.088_302_461_9f64.mul_add(r, 0.281_718_837_6f64.mul_add(g, 0.629_978_700_5 * b));
+
+        let (l_, m_, s_) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+        Some((
+            0.210_454_255_3f64.mul_add(l_, 0.793_617_785_0f64.mul_add(m_, -0.004_072_046_8 * s_)),
+            1.977_998_495_1f64.mul_add(l_, (-2.428_592_205_0f64).mul_add(m_, 0.450_593_709_9 * s_)),
+            0.025_904_037_1f64.mul_add(l_, 0.782_771_766_2f64.mul_add(m_, -0.808_675_766_0 * s_)),
+        ))
+    }
+
+    /// Returns the red, green and blue components of this color from 0.0 to
+    /// 1.0, or `None` for `Current` and `Reset`.
+    fn rgb_components(self) -> Option<(f64, f64, f64)> {
+        match self.to_rgb() {
+            Self::Rgb(r, g, b) => Some((
+                f64::from(r) / 255.0,
+                f64::from(g) / 255.0,
+                f64::from(b) / 255.0,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the linear (gamma expanded) red, green and blue components of
+    /// this color, or `None` for `Current` and `Reset`.
+    fn linear_components(self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.rgb_components()?;
+        Some((to_linear(r), to_linear(g), to_linear(b)))
+    }
+}
+
+/// Returns the RGB color with the hue `h` (in degrees), the chroma `chroma`
+/// and the amount `m` added to each component to match its lightness or
+/// value.
+fn from_hue_chroma(h: f64, chroma: f64, m: f64) -> Color {
+    let sector = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector {
+        s if s < 1.0 => (chroma, x, 0.0),
+        s if s < 2.0 => (x, chroma, 0.0),
+        s if s < 3.0 => (0.0, chroma, x),
+        s if s < 4.0 => (0.0, x, chroma),
+        s if s < 5.0 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    Color::Rgb(to_u8(r + m), to_u8(g + m), to_u8(b + m))
+}
+
+/// Returns the hue (in degrees from 0.0 to 360.0) of the RGB components
+/// `r`, `g` and `b`, or 0.0 for a gray.
+fn hue(r: f64, g: f64, b: f64) -> f64 {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+
+    if chroma == 0.0 {
+        return 0.0;
+    }
+
+    let sector = if r >= g && r >= b {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if g >= b {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    sector * 60.0
+}
+
+/// Returns the RGB color with the linear (gamma expanded) components `r`,
+/// `g` and `b`.
+fn from_linear(r: f64, g: f64, b: f64) -> Color {
+    Color::Rgb(
+        to_u8(from_linear_component(r)),
+        to_u8(from_linear_component(g)),
+        to_u8(from_linear_component(b)),
+    )
+}
+
+/// Converts an sRGB component from 0.0 to 1.0 into a linear one.
+fn to_linear(c: f64) -> f64 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear component into an sRGB component from 0.0 to 1.0.
+fn from_linear_component(c: f64) -> f64 {
+    if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055f64.mul_add(c.powf(1.0 / 2.4), -0.055)
+    }
+}
+
+/// The CIE Lab transfer function.
+fn lab_f(t: f64) -> f64 {
+    if t > LAB_EPSILON {
+        t.cbrt()
+    } else {
+        LAB_KAPPA.mul_add(t, 16.0) / 116.0
+    }
+}
+
+/// The inverse of the CIE Lab transfer function.
+fn lab_f_inverse(f: f64) -> f64 {
+    let t = f.powi(3);
+
+    if t > LAB_EPSILON {
+        t
+    } else {
+        116.0f64.mul_add(f, -16.0) / LAB_KAPPA
+    }
+}
+
+/// Converts a component from 0.0 to 1.0 into a `u8`, clamping it to its
+/// range first.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_u8(c: f64) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Color;
+
+    /// Returns RGB colors spread over the whole RGB cube.
+    fn sample_colors() -> impl Iterator<Item = Color> {
+        (0..=255).step_by(15).flat_map(|r| {
+            (0..=255)
+                .step_by(15)
+                .flat_map(move |g| (0..=255).step_by(15).map(move |b| Color::Rgb(r, g, b)))
+        })
+    }
+
+    /// Asserts that the components `actual` are within 0.001 of `expected`.
+    fn assert_close(actual: Option<(f64, f64, f64)>, expected: (f64, f64, f64)) {
+        let (a, e) = (actual.unwrap(), expected);
+        assert!(
+            (a.0 - e.0).abs() < 1e-3 && (a.1 - e.1).abs() < 1e-3 && (a.2 - e.2).abs() < 1e-3,
+            "{a:?} != {e:?}"
+        );
+    }
+
+    #[test]
+    fn test_hsl_and_hsv() {
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.25), Color::Rgb(0, 128, 0));
+        assert_eq!(Color::from_hsl(-60.0, 1.0, 0.5), Color::Rgb(255, 0, 255));
+        assert_eq!(Color::from_hsl(200.0, 0.0, 0.5), Color::Rgb(128, 128, 128));
+        assert_eq!(Color::from_hsv(60.0, 1.0, 1.0), Color::Rgb(255, 255, 0));
+        assert_eq!(Color::from_hsv(420.0, 2.0, 0.5), Color::Rgb(128, 128, 0));
+
+        assert_close(Color::Rgb(0, 128, 255).to_hsl(), (209.882, 1.0, 0.5));
+        assert_close(Color::Color256(244).to_hsl(), (0.0, 0.0, 0.502));
+        assert_close(Color::Red.to_hsv(), (0.0, 1.0, 0.804));
+        assert_eq!(Color::Reset.to_hsv(), None);
+    }
+
+    #[test]
+    fn test_lab_and_oklab() {
+        assert_close(Color::Rgb(255, 255, 255).to_lab(), (100.0, 0.0, 0.0));
+        assert_close(Color::Rgb(0, 0, 0).to_lab(), (0.0, 0.0, 0.0));
+        assert_close(Color::Rgb(255, 0, 0).to_lab(), (53.241, 80.092, 67.203));
+
+        assert_close(Color::Rgb(255, 255, 255).to_oklab(), (1.0, 0.0, 0.0));
+        assert_close(Color::Rgb(255, 0, 0).to_oklab(), (0.628, 0.225, 0.126));
+        assert_close(Color::Rgb(0, 0, 255).to_oklab(), (0.452, -0.032, -0.312));
+        assert_eq!(Color::Current.to_oklab(), None);
+
+        assert_eq!(Color::from_lab(0.0, 0.0, 0.0), Color::Rgb(0, 0, 0));
+        assert_eq!(Color::from_oklab(1.0, 0.0, 0.0), Color::Rgb(255, 255, 255));
+        // Components outside of the RGB gamut are clamped.
+        assert_eq!(Color::from_oklab(2.0, 0.5, 0.5), Color::Rgb(255, 255, 0));
+    }
+
+    #[test]
+    fn test_round_trips() {
+        for color in sample_colors() {
+            let (h, s, l) = color.to_hsl().unwrap();
+            assert_eq!(Color::from_hsl(h, s, l), color);
+
+            let (h, s, v) = color.to_hsv().unwrap();
+            assert_eq!(Color::from_hsv(h, s, v), color);
+
+            let (l, a, b) = color.to_lab().unwrap();
+            assert_eq!(Color::from_lab(l, a, b), color);
+
+            let (l, a, b) = color.to_oklab().unwrap();
+            assert_eq!(Color::from_oklab(l, a, b), color);
+        }
+    }
+}
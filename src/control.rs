@@ -0,0 +1,482 @@
+//! Deciding whether the macros emit colors.
+
+use std::cell::Cell;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::stream::Stream;
+
+/// The color decision of a stream that has not been made yet.
+const UNDECIDED: u8 = 0;
+/// The color decision of a stream whose colors are disabled.
+const DISABLED: u8 = 1;
+/// The color decision of a stream whose colors are enabled.
+const ENABLED: u8 = 2;
+
+/// The cached color decisions of stdout and stderr.
+static STDOUT_COLORS: AtomicU8 = AtomicU8::new(UNDECIDED);
+static STDERR_COLORS: AtomicU8 = AtomicU8::new(UNDECIDED);
+
+/// Returns the cached color decision of `stream`.
+const fn decision(stream: Stream) -> &'static AtomicU8 {
+    match stream {
+        Stream::Stdout => &STDOUT_COLORS,
+        Stream::Stderr => &STDERR_COLORS,
+    }
+}
+
+/// Returns true if the `print_*` (for `Stream::Stdout`) or `eprint_*` (for
+/// `Stream::Stderr`) macros emit escape codes when the color choice is
+/// `ColorChoice::Auto`.
+///
+/// The decision is made independently for each stream the first time it is
+/// needed and then cached for the rest of the process: the environment
+/// variables described in the crate documentation are consulted first, and
+/// if none of them applies, colors are enabled only if the stream is a
+/// terminal. Use `set_stream_colors` to override the decision.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{set_stream_colors, stream_colors, Stream};
+///
+/// set_stream_colors(Stream::Stdout, false);
+///
+/// assert!(!stream_colors(Stream::Stdout));
+/// ```
+#[must_use]
+pub fn stream_colors(stream: Stream) -> bool {
+    let cached = decision(stream);
+
+    match cached.load(Ordering::Relaxed) {
+        UNDECIDED => {
+            let enabled = env_colors(|name| env::var_os(name))
+                .unwrap_or_else(|| stream.is_terminal());
+            // An override set while detecting wins over the detected value.
+            match cached.compare_exchange(
+                UNDECIDED,
+                if enabled { ENABLED } else { DISABLED },
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => enabled,
+                Err(current) => current == ENABLED,
+            }
+        }
+        current => current == ENABLED,
+    }
+}
+
+/// Overrides whether the `print_*` (for `Stream::Stdout`) or `eprint_*`
+/// (for `Stream::Stderr`) macros emit escape codes, replacing the detected
+/// decision for the rest of the process.
+pub fn set_stream_colors(stream: Stream, enabled: bool) {
+    decision(stream).store(if enabled { ENABLED } else { DISABLED }, Ordering::Relaxed);
+}
+
+/// Whether the macros emit escape codes.
+///
+/// The color choice of the current thread set with `scoped_color_choice`
+/// takes precedence over the process-wide color choice set with
+/// `set_color_choice`, which defaults to `Auto`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorChoice {
+    /// Emit escape codes if the environment and the stream allow it (see
+    /// `stream_colors`). The `write_*` macros always emit escape codes.
+    #[default]
+    Auto,
+    /// Always emit escape codes.
+    Always,
+    /// Never emit escape codes.
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns the value of this color choice in `COLOR_CHOICE`.
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::Auto => 0,
+            Self::Always => 1,
+            Self::Never => 2,
+        }
+    }
+
+    /// Returns the color choice of a value in `COLOR_CHOICE`.
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Always,
+            2 => Self::Never,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// The process-wide color choice.
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    /// The color choice of the current thread, if it is overridden.
+    static SCOPED_COLOR_CHOICE: Cell<Option<ColorChoice>> = const { Cell::new(None) };
+}
+
+/// Sets the process-wide color choice of the `print_*`, `eprint_*` and
+/// `write_*` macros (e.g. from a `--color=auto|always|never` flag).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{color_choice, set_color_choice, ColorChoice};
+///
+/// set_color_choice(ColorChoice::Never);
+///
+/// assert_eq!(color_choice(), ColorChoice::Never);
+/// ```
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice.to_u8(), Ordering::Relaxed);
+}
+
+/// Returns the color choice in effect on the current thread.
+#[must_use]
+pub fn color_choice() -> ColorChoice {
+    SCOPED_COLOR_CHOICE
+        .with(Cell::get)
+        .unwrap_or_else(|| ColorChoice::from_u8(COLOR_CHOICE.load(Ordering::Relaxed)))
+}
+
+/// Overrides the color choice on the current thread until the returned
+/// guard is dropped, regardless of the process-wide color choice and the
+/// environment.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{scoped_color_choice, write_styled, ColorChoice};
+///
+/// let mut buffer: Vec<u8> = vec![];
+///
+/// {
+///     let _guard = scoped_color_choice(ColorChoice::Never);
+///     write_styled!(&mut buffer, "red", "plain");
+/// }
+/// write_styled!(&mut buffer, "red", "red");
+///
+/// assert_eq!(buffer.as_slice(), b"plain\x1b[31mred\x1b[0m");
+/// ```
+pub fn scoped_color_choice(choice: ColorChoice) -> ColorChoiceGuard {
+    ColorChoiceGuard {
+        previous: SCOPED_COLOR_CHOICE.replace(Some(choice)),
+        _not_send: PhantomData,
+    }
+}
+
+/// Restores the previous color choice of the current thread when dropped.
+///
+/// Returned by `scoped_color_choice`.
+#[derive(Debug)]
+#[must_use = "the color choice is restored as soon as the guard is dropped"]
+pub struct ColorChoiceGuard {
+    previous: Option<ColorChoice>,
+    // The guard restores a thread-local value, so it must stay on its thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ColorChoiceGuard {
+    fn drop(&mut self) {
+        SCOPED_COLOR_CHOICE.set(self.previous);
+    }
+}
+
+/// Returns true if a macro writing to `stream`, or to a buffer if `stream`
+/// is `None`, emits escape codes.
+pub fn colors_enabled(stream: Option<Stream>) -> bool {
+    match color_choice() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => stream.is_none_or(stream_colors),
+    }
+}
+
+/// Returns whether the environment variables enable (`Some(true)`) or
+/// disable (`Some(false)`) color output, or `None` if they leave the decision
+/// to the caller. `var` looks up the value of an environment variable.
+///
+/// The variables are consulted in this order, and the first one that applies
+/// decides:
+///
+/// 1. `NO_COLOR` set to a non-empty value disables colors.
+/// 2. `CLICOLOR_FORCE` set to a non-empty value other than "0" enables colors.
+/// 3. `CLICOLOR` set to "0" disables colors.
+/// 4. `TERM` set to "dumb" (in any case) disables colors.
+pub fn env_colors(var: impl Fn(&str) -> Option<OsString>) -> Option<bool> {
+    if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return Some(false);
+    }
+
+    if var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+        return Some(true);
+    }
+
+    if var("CLICOLOR").is_some_and(|value| value == "0") {
+        return Some(false);
+    }
+
+    if var("TERM").is_some_and(|value| is_dumb_term(&value)) {
+        return Some(false);
+    }
+
+    None
+}
+
+/// Returns true if the value of `TERM` is "dumb", ignoring case, i.e. the
+/// terminal does not support any escape codes.
+fn is_dumb_term(term: &OsStr) -> bool {
+    term.eq_ignore_ascii_case("dumb")
+}
+
+/// The color modes that a terminal supports.
+///
+/// Colors that the terminal does not support are downgraded to the closest
+/// color of the best available mode (see `Color::to_depth`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// No colors. Color codes are omitted and only text attributes remain.
+    None,
+    /// The 8 basic and 8 bright colors.
+    Ansi16,
+    /// The 256-color mode colors.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Returns the value of this color depth in `COLOR_DEPTH`.
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::None => 1,
+            Self::Ansi16 => 2,
+            Self::Ansi256 => 3,
+            Self::TrueColor => 4,
+        }
+    }
+
+    /// Returns the color depth of a value in `COLOR_DEPTH`, or `None` if it
+    /// has not been decided yet.
+    const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::None),
+            2 => Some(Self::Ansi16),
+            3 => Some(Self::Ansi256),
+            4 => Some(Self::TrueColor),
+            _ => None,
+        }
+    }
+}
+
+/// The cached color depth of the terminal.
+static COLOR_DEPTH: AtomicU8 = AtomicU8::new(UNDECIDED);
+
+/// Returns the color depth of the terminal, which `Fg::get_ansi_code`,
+/// `Bg::get_ansi_code` and the `print_*` and `eprint_*` macros downgrade
+/// colors to.
+///
+/// The color depth is detected from the environment variables the first
+/// time it is needed (see `env_color_depth`) and then cached for the rest of
+/// the process. Use `set_color_depth` to override it.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{color_depth, set_color_depth, Bg, Color, ColorDepth};
+///
+/// set_color_depth(ColorDepth::Ansi256);
+///
+/// assert_eq!(color_depth(), ColorDepth::Ansi256);
+/// assert_eq!(Bg::get_ansi_code(&Color::Rgb(255, 0, 0)), "48;5;196");
+/// ```
+#[must_use]
+pub fn color_depth() -> ColorDepth {
+    if let Some(depth) = ColorDepth::from_u8(COLOR_DEPTH.load(Ordering::Relaxed)) {
+        return depth;
+    }
+
+    let depth = env_color_depth(|name| env::var_os(name));
+    // A color depth set while detecting wins over the detected value.
+    match COLOR_DEPTH.compare_exchange(
+        UNDECIDED,
+        depth.to_u8(),
+        Ordering::Relaxed,
+        Ordering::Relaxed,
+    ) {
+        Ok(_) => depth,
+        Err(current) => ColorDepth::from_u8(current).unwrap_or(depth),
+    }
+}
+
+/// Overrides the detected color depth of the terminal for the rest of the
+/// process.
+pub fn set_color_depth(depth: ColorDepth) {
+    COLOR_DEPTH.store(depth.to_u8(), Ordering::Relaxed);
+}
+
+/// Returns the color depth of the terminal described by the environment
+/// variables. `var` looks up the value of an environment variable.
+///
+/// The variables are consulted in this order, and the first one that applies
+/// decides:
+///
+/// 1. `COLORTERM` set to "truecolor" or "24bit" means `TrueColor`.
+/// 2. `TERM` set to "dumb" (in any case) means `None`.
+/// 3. `TERM` ending in "-direct" or containing "truecolor" or "24bit",
+///    `TERM_PROGRAM` set to `iTerm.app`, `WezTerm` or `vscode`, or
+///    `WT_SESSION` being set (Windows Terminal) means `TrueColor`.
+/// 4. `TERM` containing "256color" or `TERM_PROGRAM` set to
+///    `Apple_Terminal` means `Ansi256`.
+/// 5. Any other `TERM` (e.g. "linux" or "xterm") means `Ansi16`.
+///
+/// Without any of these variables the terminal is unknown and colors are not
+/// downgraded (`TrueColor`).
+#[must_use]
+pub fn env_color_depth(var: impl Fn(&str) -> Option<OsString>) -> ColorDepth {
+    let colorterm = var("COLORTERM").unwrap_or_default();
+    let term = var("TERM").map(|value| value.to_string_lossy().to_lowercase());
+    let term_program = var("TERM_PROGRAM").unwrap_or_default();
+
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return ColorDepth::TrueColor;
+    }
+
+    let term_has = |pattern| term.as_deref().is_some_and(|term| term.contains(pattern));
+
+    if term.as_deref().is_some_and(|term| is_dumb_term(term.as_ref())) {
+        ColorDepth::None
+    } else if term.as_deref().is_some_and(|term| term.ends_with("-direct"))
+        || term_has("truecolor")
+        || term_has("24bit")
+        || term_program == "iTerm.app"
+        || term_program == "WezTerm"
+        || term_program == "vscode"
+        || var("WT_SESSION").is_some()
+    {
+        ColorDepth::TrueColor
+    } else if term_has("256color") || term_program == "Apple_Terminal" {
+        ColorDepth::Ansi256
+    } else if term.is_some() {
+        ColorDepth::Ansi16
+    } else {
+        ColorDepth::TrueColor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use std::sync::atomic::Ordering;
+
+    use super::{
+        color_choice, colors_enabled, env_color_depth, env_colors, scoped_color_choice,
+        set_stream_colors, stream_colors, ColorChoice, ColorDepth, COLOR_CHOICE,
+    };
+    use crate::stream::Stream;
+
+    /// Returns a lookup function for an environment containing only `vars`.
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| OsString::from(value))
+        }
+    }
+
+    /// Returns the decision for an environment containing only `vars`.
+    fn decide(vars: &[(&str, &str)]) -> Option<bool> {
+        env_colors(env(vars))
+    }
+
+    #[test]
+    fn test_env_colors() {
+        assert_eq!(decide(&[]), None);
+        assert_eq!(decide(&[("TERM", "xterm-256color"), ("CLICOLOR", "1")]), None);
+
+        assert_eq!(decide(&[("NO_COLOR", "1")]), Some(false));
+        assert_eq!(decide(&[("NO_COLOR", "")]), None);
+        assert_eq!(decide(&[("CLICOLOR", "0")]), Some(false));
+        assert_eq!(decide(&[("TERM", "dumb")]), Some(false));
+        assert_eq!(decide(&[("TERM", "DUMB")]), Some(false));
+
+        assert_eq!(decide(&[("CLICOLOR_FORCE", "1")]), Some(true));
+        assert_eq!(decide(&[("CLICOLOR_FORCE", "0")]), None);
+        assert_eq!(decide(&[("CLICOLOR_FORCE", "")]), None);
+    }
+
+    #[test]
+    fn test_env_colors_precedence() {
+        assert_eq!(decide(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]), Some(false));
+        assert_eq!(decide(&[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0")]), Some(true));
+        assert_eq!(decide(&[("CLICOLOR_FORCE", "1"), ("TERM", "dumb")]), Some(true));
+        assert_eq!(decide(&[("CLICOLOR", "0"), ("TERM", "xterm")]), Some(false));
+    }
+
+    #[test]
+    fn test_stream_colors_override() {
+        set_stream_colors(Stream::Stderr, true);
+        assert!(stream_colors(Stream::Stderr));
+
+        set_stream_colors(Stream::Stderr, false);
+        assert!(!stream_colors(Stream::Stderr));
+    }
+
+    #[test]
+    fn test_scoped_color_choice() {
+        let global = ColorChoice::from_u8(COLOR_CHOICE.load(Ordering::Relaxed));
+
+        let _always = scoped_color_choice(ColorChoice::Always);
+        assert!(colors_enabled(None));
+
+        {
+            let _never = scoped_color_choice(ColorChoice::Never);
+            assert!(!colors_enabled(None));
+            assert!(!colors_enabled(Some(Stream::Stdout)));
+
+            {
+                let _auto = scoped_color_choice(ColorChoice::Auto);
+                assert_eq!(color_choice(), ColorChoice::Auto);
+                assert!(colors_enabled(None));
+            }
+            assert_eq!(color_choice(), ColorChoice::Never);
+        }
+        assert_eq!(color_choice(), ColorChoice::Always);
+
+        // Other threads are not affected by a scoped color choice.
+        std::thread::spawn(move || assert_eq!(color_choice(), global))
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_env_color_depth() {
+        let depth = |vars: &[(&str, &str)]| env_color_depth(env(vars));
+
+        assert_eq!(depth(&[]), ColorDepth::TrueColor);
+        assert_eq!(depth(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]), ColorDepth::TrueColor);
+        assert_eq!(depth(&[("COLORTERM", "24bit"), ("TERM", "dumb")]), ColorDepth::TrueColor);
+        assert_eq!(depth(&[("TERM", "dumb")]), ColorDepth::None);
+        assert_eq!(depth(&[("TERM", "Dumb")]), ColorDepth::None);
+        assert_eq!(depth(&[("TERM", "xterm-direct")]), ColorDepth::TrueColor);
+        assert_eq!(depth(&[("TERM", "xterm"), ("WT_SESSION", "1")]), ColorDepth::TrueColor);
+        assert_eq!(
+            depth(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "iTerm.app")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(depth(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(depth(&[("TERM", "screen.xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(
+            depth(&[("TERM", "xterm"), ("TERM_PROGRAM", "Apple_Terminal")]),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(depth(&[("TERM", "linux")]), ColorDepth::Ansi16);
+        assert_eq!(depth(&[("TERM", "screen"), ("COLORTERM", "1")]), ColorDepth::Ansi16);
+    }
+}
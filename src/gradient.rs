@@ -0,0 +1,261 @@
+//! Text whose color changes gradually from one grapheme to the next.
+
+use std::fmt::{self, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::parse::Codes;
+use crate::{control, Color, ColorDepth};
+
+/// The color space in which a `Gradient` interpolates between its stops.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// Interpolate each RGB component linearly.
+    #[default]
+    Rgb,
+    /// Interpolate in the Oklab color space, which avoids the dull or dark
+    /// colors that RGB interpolation produces between distant hues.
+    Oklab,
+}
+
+/// A color gradient across any number of color stops.
+///
+/// The stops are spread evenly over the text, and each grapheme (a
+/// user-perceived character such as "é" or "👍🏽") gets the color between
+/// the stops at its position. A gradient colors the foreground by default.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Color, Gradient, Interpolation};
+///
+/// let gradient = Gradient::new([Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)]);
+///
+/// assert_eq!(gradient.color_at(0.5), Some(Color::Rgb(128, 0, 128)));
+/// assert_eq!(
+///     gradient.apply("abc").to_string(),
+///     "\x1b[38;2;255;0;0ma\x1b[38;2;128;0;128mb\x1b[38;2;0;0;255mc\x1b[0m"
+/// );
+///
+/// let gradient = gradient.interpolation(Interpolation::Oklab).background();
+///
+/// assert_eq!(gradient.color_at(0.5), Some(Color::Rgb(140, 83, 162)));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Gradient {
+    stops: Vec<Color>,
+    interpolation: Interpolation,
+    background: bool,
+}
+
+impl Gradient {
+    /// Returns a new foreground gradient through `stops` that interpolates
+    /// in RGB.
+    ///
+    /// Basic, bright and 256-color mode stops are converted into RGB colors
+    /// (see `Color::to_rgb`), and `Current` and `Reset` stops are ignored.
+    #[must_use]
+    pub fn new<I: IntoIterator<Item = Color>>(stops: I) -> Self {
+        Self {
+            stops: stops
+                .into_iter()
+                .map(Color::to_rgb)
+                .filter(|stop| matches!(stop, Color::Rgb(..)))
+                .collect(),
+            interpolation: Interpolation::Rgb,
+            background: false,
+        }
+    }
+
+    /// Returns this gradient with its stops interpolated in `interpolation`.
+    #[must_use]
+    pub const fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Returns this gradient coloring the background instead of the
+    /// foreground.
+    #[must_use]
+    pub const fn background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+
+    /// Returns the color at the position `t` (from 0.0 at the first stop to
+    /// 1.0 at the last stop), or `None` if the gradient has no stops.
+    #[must_use]
+    pub fn color_at(&self, t: f64) -> Option<Color> {
+        let (&first, rest) = self.stops.split_first()?;
+        if rest.is_empty() {
+            return Some(first);
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let position = t.clamp(0.0, 1.0) * rest.len() as f64;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let idx = (position.floor() as usize).min(rest.len() - 1);
+        #[allow(clippy::cast_precision_loss)]
+        let local = position - idx as f64;
+
+        Some(self.mix(self.stops[idx], self.stops[idx + 1], local))
+    }
+
+    /// Returns the color a fraction `t` of the way from `from` to `to`.
+    fn mix(&self, from: Color, to: Color, t: f64) -> Color {
+        let lerp = |a: f64, b: f64| (b - a).mul_add(t, a);
+
+        match self.interpolation {
+            Interpolation::Rgb => match (from, to) {
+                (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let mix = |a: u8, b: u8| lerp(f64::from(a), f64::from(b)).round() as u8;
+                    Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+                }
+                _ => from,
+            },
+            Interpolation::Oklab => match (from.to_oklab(), to.to_oklab()) {
+                (Some((l1, a1, b1)), Some((l2, a2, b2))) => {
+                    Color::from_oklab(lerp(l1, l2), lerp(a1, a2), lerp(b1, b2))
+                }
+                _ => from,
+            },
+        }
+    }
+
+    /// Returns `text` with this gradient applied to it, which can be used
+    /// anywhere a `Display` is accepted.
+    ///
+    /// No escape codes are written when the color choice is
+    /// `ColorChoice::Never`. Like the `write_*` macros, the text is not tied to
+    /// a terminal, so its colors are never downgraded to the color depth;
+    /// use `print_gradient!()` for that.
+    #[must_use]
+    pub const fn apply<'a>(&'a self, text: &'a str) -> GradientText<'a> {
+        GradientText { gradient: self, text }
+    }
+
+    /// Appends `text` with this gradient applied to it to `out`, downgrading
+    /// the colors to `depth`. `None` appends the plain text.
+    pub(crate) fn render(&self, out: &mut String, text: &str, depth: Option<ColorDepth>) {
+        let Some(depth) = depth.filter(|_| !self.stops.is_empty()) else {
+            out.push_str(text);
+            return;
+        };
+
+        let count = text.graphemes(true).count();
+        let mut previous = Codes::new();
+
+        for (idx, grapheme) in text.graphemes(true).enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let t = if count > 1 { idx as f64 / (count - 1) as f64 } else { 0.0 };
+            let codes = self
+                .color_at(t)
+                .unwrap_or(Color::Current)
+                .to_depth(depth)
+                .push_ansi_code(Codes::new(), self.background);
+
+            // Neighboring graphemes often share a color after downgrading.
+            if !codes.is_empty() && codes.as_str() != previous.as_str() {
+                let _ = write!(out, "\u{001b}[{}m", codes.as_str());
+                previous = codes;
+            }
+            out.push_str(grapheme);
+        }
+
+        if !previous.is_empty() {
+            out.push_str("\u{001b}[0m");
+        }
+    }
+}
+
+/// Text with a `Gradient` applied to it.
+///
+/// Returned by `Gradient::apply`. It is displayed with 24-bit RGB colors
+/// regardless of `color_depth()`.
+#[derive(Clone, Copy, Debug)]
+pub struct GradientText<'a> {
+    gradient: &'a Gradient,
+    text: &'a str,
+}
+
+impl fmt::Display for GradientText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let depth = control::colors_enabled(None).then_some(ColorDepth::TrueColor);
+        let mut out = String::with_capacity(self.text.len() * 20);
+
+        self.gradient.render(&mut out, self.text, depth);
+        f.write_str(&out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gradient, Interpolation};
+    use crate::{Color, ColorDepth};
+
+    /// Returns `text` with `gradient` applied to it at the color depth
+    /// `depth`.
+    fn render(gradient: &Gradient, text: &str, depth: Option<ColorDepth>) -> String {
+        let mut out = String::new();
+        gradient.render(&mut out, text, depth);
+        out
+    }
+
+    #[test]
+    fn test_color_at() {
+        let gradient = Gradient::new([
+            Color::Rgb(255, 0, 0),
+            Color::Rgb(0, 255, 0),
+            Color::Rgb(0, 0, 255),
+        ]);
+
+        assert_eq!(gradient.color_at(0.0), Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(gradient.color_at(0.25), Some(Color::Rgb(128, 128, 0)));
+        assert_eq!(gradient.color_at(0.5), Some(Color::Rgb(0, 255, 0)));
+        assert_eq!(gradient.color_at(1.0), Some(Color::Rgb(0, 0, 255)));
+        assert_eq!(gradient.color_at(7.0), Some(Color::Rgb(0, 0, 255)));
+
+        let gradient = Gradient::new([Color::Black, Color::Current, Color::Color256(231)])
+            .interpolation(Interpolation::Oklab);
+        assert_eq!(gradient.color_at(0.0), Some(Color::Rgb(0, 0, 0)));
+        assert_eq!(gradient.color_at(0.5), Some(Color::Rgb(99, 99, 99)));
+        assert_eq!(gradient.color_at(1.0), Some(Color::Rgb(255, 255, 255)));
+
+        assert_eq!(Gradient::new([Color::Red]).color_at(0.5), Some(Color::Rgb(205, 0, 0)));
+        assert_eq!(Gradient::new([]).color_at(0.5), None);
+    }
+
+    #[test]
+    fn test_render_per_grapheme() {
+        let gradient = Gradient::new([Color::Rgb(0, 0, 0), Color::Rgb(0, 0, 200)]).background();
+
+        // "e\u{301}" is a single grapheme made of two chars.
+        assert_eq!(
+            render(&gradient, "ae\u{301}b", Some(ColorDepth::TrueColor)),
+            concat!(
+                "\x1b[48;2;0;0;0ma",
+                "\x1b[48;2;0;0;100me\u{301}",
+                "\x1b[48;2;0;0;200mb\x1b[0m",
+            )
+        );
+        assert_eq!(
+            render(&gradient, "x", Some(ColorDepth::TrueColor)),
+            "\x1b[48;2;0;0;0mx\x1b[0m"
+        );
+        assert_eq!(render(&gradient, "", Some(ColorDepth::TrueColor)), "");
+    }
+
+    #[test]
+    fn test_render_downgrades_and_plain_text() {
+        let gradient = Gradient::new([Color::Rgb(255, 0, 0), Color::Rgb(250, 0, 0)]);
+
+        assert_eq!(
+            render(&gradient, "abc", Some(ColorDepth::Ansi16)),
+            "\x1b[91mabc\x1b[0m"
+        );
+        assert_eq!(render(&gradient, "abc", Some(ColorDepth::None)), "abc");
+        assert_eq!(render(&gradient, "abc", None), "abc");
+        assert_eq!(render(&Gradient::new([]), "abc", Some(ColorDepth::TrueColor)), "abc");
+    }
+}
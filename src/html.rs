@@ -0,0 +1,509 @@
+//! Conversion of styled text into HTML.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::ansi::{self, Token};
+use crate::color::palette_num;
+use crate::{Attribute, Color, Palette, Style};
+
+/// The class names of the basic and bright colors, in the order of their
+/// 256-color mode color numbers.
+const CLASS_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// The attributes that are drawn with a font property, with the CSS property
+/// and its value. SVG presentation attributes share these names.
+pub const FONT_PROPERTIES: [(Attribute, &str, &str); 3] = [
+    (Attribute::Bold, "font-weight", "bold"),
+    (Attribute::Dim, "opacity", "0.5"),
+    (Attribute::Italic, "font-style", "italic"),
+];
+
+/// The attributes that are drawn with the `text-decoration` CSS property,
+/// with their values of the property.
+pub const DECORATIONS: [(Attribute, &str); 3] = [
+    (Attribute::Underline, "underline"),
+    (Attribute::Strikethrough, "line-through"),
+    (Attribute::Blink, "blink"),
+];
+
+/// Converts text containing SGR escape sequences (e.g. the output of the
+/// write macros) into HTML with inline styles, using the default `Html`
+/// options.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{ansi_to_html, write_styled};
+///
+/// let mut buffer: Vec<u8> = vec![];
+/// write_styled!(&mut buffer, "bold red", "<error>");
+/// let text = String::from_utf8(buffer).unwrap();
+///
+/// assert_eq!(
+///     ansi_to_html(&text),
+///     "<span style=\"color:#cd0000;font-weight:bold\">&lt;error&gt;</span>"
+/// );
+/// ```
+#[must_use]
+pub fn ansi_to_html(text: &str) -> String {
+    Html::new().apply(text)
+}
+
+/// Options for converting styled text into HTML.
+///
+/// Each run of text with a style becomes a `<span>` element, and the text is
+/// escaped. Other escape sequences are removed. Whitespace and newlines are
+/// kept as they are, so the HTML is meant to be placed in a `<pre>` element.
+///
+/// By default, colors and attributes are written as inline CSS, and the
+/// basic and bright colors are displayed with the colors of the `Palette`.
+/// With `Html::classes`, the basic and bright colors and the attributes are
+/// written as class names instead, and `Html::stylesheet` returns the CSS
+/// rules for them. The 256-color mode colors 16 - 255 and RGB colors are
+/// always written as inline CSS. Reversed colors are swapped, using the
+/// default colors of the palette if a color is not set.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Html, Palette};
+///
+/// let html = Html::new().classes("ansi-");
+///
+/// assert_eq!(
+///     html.apply("\x1b[1;31mred\x1b[0m \x1b[38;5;208morange\x1b[0m"),
+///     "<span class=\"ansi-red ansi-bold\">red</span> \
+///      <span style=\"color:#ff8700\">orange</span>"
+/// );
+/// assert!(html.stylesheet().contains(".ansi-red { color: #cd0000; }"));
+///
+/// let html = Html::new().palette(Palette::XTERM.with_background((40, 44, 52)));
+///
+/// assert_eq!(
+///     html.apply("\x1b[7mreversed\x1b[0m"),
+///     "<span style=\"color:#282c34;background-color:#e5e5e5\">reversed</span>"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Html<'a> {
+    palette: Palette,
+    class_prefix: Option<&'a str>,
+}
+
+impl<'a> Html<'a> {
+    /// Returns the default options, which write inline CSS with the colors
+    /// of `Palette::XTERM`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { palette: Palette::XTERM, class_prefix: None }
+    }
+
+    /// Returns these options with the basic and bright colors and the default
+    /// colors displayed with the colors of `palette`.
+    #[must_use]
+    pub const fn palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Returns these options with the basic and bright colors and the
+    /// attributes written as class names that start with `prefix` (e.g.
+    /// "ansi-red", "ansi-bg-bright-blue" and "ansi-bold" for "ansi-").
+    #[must_use]
+    pub const fn classes(mut self, prefix: &'a str) -> Self {
+        self.class_prefix = Some(prefix);
+        self
+    }
+
+    /// Returns `text` converted into HTML.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() * 2);
+        self.render(&mut out, text, &mut Style::new());
+        out
+    }
+
+    /// Returns the CSS rules for the class names written with
+    /// `Html::classes`, using the colors of the palette, or an empty string
+    /// if class names are not written.
+    #[must_use]
+    pub fn stylesheet(&self) -> String {
+        let Some(prefix) = self.class_prefix else {
+            return String::new();
+        };
+        let mut css = String::new();
+
+        for (num, name) in (0..).zip(CLASS_NAMES) {
+            let (r, g, b) = self.palette.rgb(Color::Color256(num)).unwrap_or_default();
+            let _ = writeln!(css, ".{prefix}{name} {{ color: #{r:02x}{g:02x}{b:02x}; }}");
+            let _ = writeln!(
+                css,
+                ".{prefix}bg-{name} {{ background-color: #{r:02x}{g:02x}{b:02x}; }}"
+            );
+        }
+
+        for attr in Attribute::ALL {
+            if let Some(declaration) = attribute_css(attr) {
+                let _ = writeln!(css, ".{prefix}{} {{ {declaration}; }}", attr.name());
+            }
+        }
+
+        // A class can only set one value of `text-decoration`, so each
+        // combination of the attributes that set it needs its own rule.
+        for bits in 1_usize..(1 << DECORATIONS.len()) {
+            if bits.count_ones() < 2 {
+                continue;
+            }
+            let (selector, values): (Vec<String>, Vec<&str>) = DECORATIONS
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| bits & (1 << idx) != 0)
+                .map(|(_, (attr, value))| (format!(".{prefix}{}", attr.name()), *value))
+                .unzip();
+            let _ = writeln!(
+                css,
+                "{} {{ text-decoration: {}; }}",
+                selector.concat(),
+                values.join(" ")
+            );
+        }
+        css
+    }
+
+    /// Appends `text` converted into HTML to `out`, starting with the style
+    /// `style` and leaving it set to the style at the end of `text`. Every
+    /// `<span>` element is closed at the end.
+    fn render(&self, out: &mut String, text: &str, style: &mut Style) {
+        // The style of the open `<span>` element, if any.
+        let mut open: Option<Style> = None;
+
+        for token in ansi::tokens(text) {
+            let run = match token {
+                Token::Escape(sequence) => {
+                    *style = ansi::apply_escape(*style, sequence);
+                    continue;
+                }
+                Token::Text(run) => run,
+            };
+
+            if open.unwrap_or_default() != *style {
+                if open.take().is_some() {
+                    out.push_str("</span>");
+                }
+                if !style.is_plain() {
+                    self.open_span(out, *style);
+                    open = Some(*style);
+                }
+            }
+            escape(out, run);
+        }
+
+        if open.is_some() {
+            out.push_str("</span>");
+        }
+    }
+
+    /// Appends the opening `<span>` tag of `style` to `out`.
+    fn open_span(&self, out: &mut String, style: Style) {
+        let (fg, bg) = resolved_colors(style, &self.palette);
+
+        let mut classes: Vec<String> = Vec::new();
+        let mut css: Vec<String> = Vec::new();
+
+        for (color, property, class) in [(fg, "color", ""), (bg, "background-color", "bg-")] {
+            let Some(color) = color else {
+                continue;
+            };
+            match (self.class_prefix, palette_num(color)) {
+                (Some(prefix), Some(num)) => {
+                    classes.push(format!("{prefix}{class}{}", CLASS_NAMES[num as usize]));
+                }
+                _ => {
+                    if let Some((r, g, b)) = self.palette.rgb(color) {
+                        css.push(format!("{property}:#{r:02x}{g:02x}{b:02x}"));
+                    }
+                }
+            }
+        }
+
+        let attrs = style.attrs.iter().filter(|attr| *attr != Attribute::Reverse);
+
+        if let Some(prefix) = self.class_prefix {
+            classes.extend(attrs.map(|attr| format!("{prefix}{}", attr.name())));
+        } else {
+            let decorations = decorations(style);
+
+            css.extend(attrs.filter_map(attribute_css));
+            if !decorations.is_empty() {
+                css.push(format!("text-decoration:{}", decorations.join(" ")));
+            }
+        }
+
+        out.push_str("<span");
+        if !classes.is_empty() {
+            let _ = write!(out, " class=\"{}\"", classes.join(" "));
+        }
+        if !css.is_empty() {
+            let _ = write!(out, " style=\"{}\"", css.join(";"));
+        }
+        out.push('>');
+    }
+}
+
+impl Default for Html<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the foreground and background colors that `style` is drawn with,
+/// which `Reverse` swaps. A swapped default color is taken from `palette`.
+pub fn resolved_colors(style: Style, palette: &Palette) -> (Option<Color>, Option<Color>) {
+    if !style.attrs.contains(Attribute::Reverse) {
+        return (style.fg, style.bg);
+    }
+
+    let rgb = |(r, g, b)| Color::Rgb(r, g, b);
+    (
+        Some(style.bg.unwrap_or_else(|| rgb(palette.background()))),
+        Some(style.fg.unwrap_or_else(|| rgb(palette.foreground()))),
+    )
+}
+
+/// Returns the `text-decoration` values of the attributes of `style`.
+pub fn decorations(style: Style) -> Vec<&'static str> {
+    DECORATIONS
+        .iter()
+        .filter(|(attr, _)| style.attrs.contains(*attr))
+        .map(|(_, value)| *value)
+        .collect()
+}
+
+/// Returns the CSS declaration of an attribute that is not drawn with the
+/// `text-decoration` property, or `None` for those that are and `Reverse`.
+fn attribute_css(attr: Attribute) -> Option<String> {
+    if attr == Attribute::Hidden {
+        return Some("color:transparent".to_string());
+    }
+
+    FONT_PROPERTIES
+        .iter()
+        .find(|(font_attr, ..)| *font_attr == attr)
+        .map(|(_, property, value)| format!("{property}:{value}"))
+}
+
+/// Appends `text` to `out` with the characters that are special in HTML
+/// escaped.
+pub fn escape(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// A writer that converts the styled text written to it into HTML before
+/// writing it to the inner writer.
+///
+/// The `write_*` macros can write to an `HtmlWriter` directly. Escape
+/// sequences and characters that are split across several calls to `write`
+/// are kept until the rest of them is written, and the style at the end of
+/// one write carries over to the next one. Every `<span>` element is closed
+/// at the end of each write, so the HTML written so far is always complete.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{write_styled, HtmlWriter};
+///
+/// let mut page = HtmlWriter::new(Vec::new());
+///
+/// write_styled!(&mut page, "green", "ok");
+/// write_styled!(&mut page, "current", " & done");
+///
+/// assert_eq!(
+///     String::from_utf8(page.into_inner()).unwrap(),
+///     "<span style=\"color:#00cd00\">ok</span> &amp; done"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct HtmlWriter<'a, W: Write> {
+    inner: W,
+    html: Html<'a>,
+    /// The style at the end of the text written so far.
+    style: Style,
+    /// The bytes of a cut off escape sequence or character.
+    pending: Vec<u8>,
+}
+
+impl<'a, W: Write> HtmlWriter<'a, W> {
+    /// Returns a new `HtmlWriter` that writes to `inner` with the default
+    /// `Html` options.
+    pub const fn new(inner: W) -> Self {
+        Self::with_options(inner, Html::new())
+    }
+
+    /// Returns a new `HtmlWriter` that writes to `inner` with the options
+    /// `html`.
+    pub const fn with_options(inner: W, html: Html<'a>) -> Self {
+        Self { inner, html, style: Style::new(), pending: Vec::new() }
+    }
+
+    /// Returns a reference to the inner writer.
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    pub const fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the inner writer. An escape sequence or character that was
+    /// cut off by the last write is discarded.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HtmlWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        let complete = self.pending.len() - cut_off_char_len(&self.pending);
+        let text = String::from_utf8_lossy(&self.pending[..complete]);
+
+        // Keep an escape sequence that the rest of may still be written.
+        let len = match ansi::tokens(&text).last() {
+            Some(Token::Escape(sequence)) if !ansi::is_complete(sequence) => {
+                text.len() - sequence.len()
+            }
+            _ => text.len(),
+        };
+
+        let mut out = String::with_capacity(len * 2);
+        self.html.render(&mut out, &text[..len], &mut self.style);
+
+        // The kept escape sequence is ASCII, so its length is the same in
+        // `text` and in `pending`.
+        let kept = complete - (text.len() - len);
+        self.pending.drain(..kept);
+
+        self.inner.write_all(out.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns the number of bytes at the end of `bytes` that are the start of a
+/// UTF-8 encoded character that is cut off.
+fn cut_off_char_len(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+
+    for (idx, &byte) in bytes.iter().enumerate().skip(start).rev() {
+        let len = match byte {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            // A continuation byte.
+            0x80..=0xbf => continue,
+            _ => return 0,
+        };
+        return if bytes.len() - idx < len { bytes.len() - idx } else { 0 };
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{ansi_to_html, Html, HtmlWriter};
+    use crate::{write_styled, Color, Palette};
+
+    #[test]
+    fn test_ansi_to_html() {
+        assert_eq!(ansi_to_html("a < b && \"c\" 'd'"), "a &lt; b &amp;&amp; &quot;c&quot; &#39;d&#39;");
+        assert_eq!(
+            ansi_to_html("\x1b[3;4;9;94;48;2;1;2;3mx\x1b[22m\x1b[2Ky\x1b[0mz"),
+            "<span style=\"color:#5c5cff;background-color:#010203;font-style:italic;\
+             text-decoration:underline line-through\">xy</span>z"
+        );
+        assert_eq!(
+            ansi_to_html("\x1b[31ma\x1b[1mb\x1b[0m"),
+            "<span style=\"color:#cd0000\">a</span>\
+             <span style=\"color:#cd0000;font-weight:bold\">b</span>"
+        );
+        assert_eq!(
+            ansi_to_html("\x1b[7;31mx\x1b[0m"),
+            "<span style=\"color:#000000;background-color:#cd0000\">x</span>"
+        );
+        assert_eq!(ansi_to_html("\x1b[31m\x1b[0m"), "");
+    }
+
+    #[test]
+    fn test_html_options() {
+        let palette = Palette::XTERM.with(Color::Red, (255, 85, 85));
+        let html = Html::new().palette(palette);
+
+        assert_eq!(html.apply("\x1b[31mx"), "<span style=\"color:#ff5555\">x</span>");
+        assert_eq!(Html::new().stylesheet(), "");
+
+        let html = html.classes("c-");
+        let css = html.stylesheet();
+
+        assert_eq!(
+            html.apply("\x1b[1;4;9;101;38;5;1mx\x1b[0m"),
+            "<span class=\"c-red c-bg-bright-red c-bold c-underline c-strikethrough\">x</span>"
+        );
+        assert!(css.contains(".c-red { color: #ff5555; }\n"));
+        assert!(css.contains(".c-bg-bright-white { background-color: #ffffff; }\n"));
+        assert!(css.contains(".c-italic { font-style:italic; }\n"));
+        assert!(css.contains(".c-underline.c-strikethrough { text-decoration: underline line-through; }\n"));
+    }
+
+    #[test]
+    fn test_html_writer_split_writes() {
+        let input = "\x1b[32mé\x1b[0m \x1b]0;t\x07日\x1b[1m!";
+        let expected = ansi_to_html(input);
+
+        for split in 1..input.len() {
+            let mut writer = HtmlWriter::new(Vec::new());
+            writer.write_all(&input.as_bytes()[..split]).unwrap();
+            writer.write_all(&input.as_bytes()[split..]).unwrap();
+
+            let html = String::from_utf8(writer.into_inner()).unwrap();
+            assert_eq!(html, expected, "split at {split}");
+        }
+
+        let mut writer = HtmlWriter::with_options(Vec::new(), Html::new().classes("a-"));
+        write_styled!(&mut writer, "bold blue", "<b>");
+        assert_eq!(writer.get_ref().as_slice(), b"<span class=\"a-blue a-bold\">&lt;b&gt;</span>");
+    }
+}
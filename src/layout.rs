@@ -0,0 +1,509 @@
+//! Functions that lay out styled text by its width on the terminal.
+
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::ansi::{self, Token};
+use crate::Style;
+
+/// The ellipsis that `truncate_with_ellipsis` ends truncated text with.
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Returns the number of terminal columns that `text` takes up.
+///
+/// Escape sequences take up no columns, wide characters (e.g. CJK characters
+/// and most emoji) take up two columns according to their Unicode East Asian
+/// width, and combining characters and control characters take up none. This
+/// is the width that `format!("{:<10}", ...)` gets wrong for styled text,
+/// which counts the bytes of its escape sequences as characters.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{display_width, Colorize};
+///
+/// let text = "日本".red().to_string();
+///
+/// assert_eq!(text.chars().count(), 11);
+/// assert_eq!(display_width(&text), 4);
+/// assert_eq!(display_width("e\u{301}"), 1);
+/// ```
+#[must_use]
+pub fn display_width(text: &str) -> usize {
+    ansi::tokens(text)
+        .map(|token| match token {
+            Token::Text(run) => run.graphemes(true).map(grapheme_width).sum(),
+            Token::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// Returns `text` padded with spaces on the right to `width` columns, or
+/// `text` itself if it is already at least as wide.
+///
+/// If a style is still active at the end of `text`, it is reset before the
+/// padding so that the padding is not colored.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{pad_to, Colorize};
+///
+/// let text = "ok".green().to_string();
+///
+/// assert_eq!(pad_to(&text, 4), "\x1b[32mok\x1b[0m  ");
+/// assert_eq!(pad_to("日本", 5), "日本 ");
+/// ```
+#[must_use]
+pub fn pad_to(text: &str, width: usize) -> Cow<'_, str> {
+    pad(text, width, 0)
+}
+
+/// Returns `text` centered in `width` columns by padding it with spaces on
+/// both sides, or `text` itself if it is already at least as wide.
+///
+/// If the padding cannot be split evenly, the extra space goes on the right.
+/// If a style is still active at the end of `text`, it is reset before the
+/// padding on the right.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{center, Colorize};
+///
+/// assert_eq!(center(&"ok".bold().to_string(), 5), " \x1b[1mok\x1b[0m  ");
+/// ```
+#[must_use]
+pub fn center(text: &str, width: usize) -> Cow<'_, str> {
+    let left = width.saturating_sub(display_width(text)) / 2;
+    pad(text, width, left)
+}
+
+/// Returns `text` truncated to at most `width` columns, ending with an
+/// ellipsis ("…") if any of it was cut off, or `text` itself if it fits.
+///
+/// Escape sequences before the cut are kept, so the remaining text and the
+/// ellipsis keep their styles, and a style that is active at the cut is
+/// reset after the ellipsis. A wide character that does not fit in the
+/// remaining columns is cut off as a whole.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{truncate_with_ellipsis, Colorize};
+///
+/// let text = "warning".yellow().to_string();
+///
+/// assert_eq!(truncate_with_ellipsis(&text, 5), "\x1b[33mwarn…\x1b[0m");
+/// assert_eq!(truncate_with_ellipsis(&text, 7), text);
+/// assert_eq!(truncate_with_ellipsis("日本語", 4), "日…");
+/// ```
+#[must_use]
+pub fn truncate_with_ellipsis(text: &str, width: usize) -> Cow<'_, str> {
+    if display_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+    if width == 0 {
+        return Cow::Borrowed("");
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut style = Style::new();
+    // The columns that are left for the text before the ellipsis.
+    let mut remaining = width - ELLIPSIS.width();
+
+    'tokens: for token in ansi::tokens(text) {
+        match token {
+            Token::Escape(sequence) => {
+                out.push_str(sequence);
+                style = ansi::apply_escape(style, sequence);
+            }
+            Token::Text(run) => {
+                for grapheme in run.graphemes(true) {
+                    let Some(rest) = remaining.checked_sub(grapheme_width(grapheme)) else {
+                        break 'tokens;
+                    };
+                    out.push_str(grapheme);
+                    remaining = rest;
+                }
+            }
+        }
+    }
+
+    out.push_str(ELLIPSIS);
+    if !style.is_plain() {
+        out.push_str("\u{001b}[0m");
+    }
+    Cow::Owned(out)
+}
+
+/// Returns `text` wrapped at word boundaries so that each line takes up at
+/// most `width` columns.
+///
+/// The style that is active where a line is broken is reset at the end of
+/// the line and turned on again at the start of the next one, so a
+/// background color does not bleed into the rest of the terminal line. Use
+/// `Wrap` to indent the lines.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{wrap, Colorize};
+///
+/// let text = "white on red".white().on_red().to_string();
+///
+/// assert_eq!(wrap(&text, 8), "\x1b[37;41mwhite on\x1b[0m\n\x1b[37;41mred\x1b[0m");
+/// ```
+#[must_use]
+pub fn wrap(text: &str, width: usize) -> String {
+    Wrap::new(width).apply(text)
+}
+
+/// Options for wrapping styled text at word boundaries.
+///
+/// Lines are broken at whitespace, which is dropped at the break, and words
+/// that are wider than a line are broken between graphemes. Each line of
+/// the text (i.e. each line ending with "\n") is wrapped separately and
+/// starts with the indent, and the lines it is broken into start with the
+/// hanging indent. The indents are not styled and count towards the width.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::Wrap;
+///
+/// let usage = Wrap::new(20).indent("- ").hanging_indent("  ");
+///
+/// assert_eq!(
+///     usage.apply("a list item that is wrapped\nanother item"),
+///     "- a list item that\n  is wrapped\n- another item"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Wrap<'a> {
+    width: usize,
+    indent: &'a str,
+    hanging_indent: &'a str,
+}
+
+impl<'a> Wrap<'a> {
+    /// Returns options for wrapping text at `width` columns without indents.
+    #[must_use]
+    pub const fn new(width: usize) -> Self {
+        Self { width, indent: "", hanging_indent: "" }
+    }
+
+    /// Returns these options with the first line of each line of the text
+    /// indented by `indent`.
+    #[must_use]
+    pub const fn indent(mut self, indent: &'a str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Returns these options with the lines that a line of the text is
+    /// broken into, other than the first one, indented by `indent`.
+    #[must_use]
+    pub const fn hanging_indent(mut self, indent: &'a str) -> Self {
+        self.hanging_indent = indent;
+        self
+    }
+
+    /// Returns `text` wrapped with these options.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        let mut wrapper = Wrapper {
+            options: self,
+            out: String::with_capacity(text.len() + text.len() / 8),
+            style: Style::new(),
+            indent: self.indent,
+            started: false,
+            line_width: 0,
+        };
+
+        for (idx, line) in text.split('\n').enumerate() {
+            if idx > 0 {
+                wrapper.break_line(self.indent);
+            }
+            wrapper.push_line(line);
+        }
+        wrapper.out
+    }
+}
+
+/// The state of wrapping a text.
+struct Wrapper<'o, 'a> {
+    options: &'o Wrap<'a>,
+    out: String,
+    /// The style that is active at the end of `out`.
+    style: Style,
+    /// The indent of the current line.
+    indent: &'a str,
+    /// Whether the indent of the current line has been written.
+    started: bool,
+    /// The columns taken up by the current line, without its indent.
+    line_width: usize,
+}
+
+impl<'a> Wrapper<'_, 'a> {
+    /// Wraps a line of text that contains no newlines.
+    fn push_line(&mut self, line: &str) {
+        // The whitespace and the word that follows it, with any escape
+        // sequences in them, and the columns they take up.
+        let mut space: Vec<Token<'_>> = Vec::new();
+        let mut word: Vec<Token<'_>> = Vec::new();
+        let (mut space_width, mut word_width) = (0, 0);
+
+        for token in ansi::tokens(line) {
+            let Token::Text(run) = token else {
+                if word.is_empty() {
+                    space.push(token);
+                } else {
+                    word.push(token);
+                }
+                continue;
+            };
+
+            for (is_space, part) in split_spaces(run) {
+                let width = part.graphemes(true).map(grapheme_width).sum::<usize>();
+
+                if !is_space {
+                    word.push(Token::Text(part));
+                    word_width += width;
+                } else if word.is_empty() {
+                    space.push(Token::Text(part));
+                    space_width += width;
+                } else {
+                    self.push_word(&space, space_width, &word, word_width);
+                    space = vec![Token::Text(part)];
+                    word.clear();
+                    (space_width, word_width) = (width, 0);
+                }
+            }
+        }
+
+        if word.is_empty() {
+            // Trailing whitespace is dropped, but not its escape sequences.
+            space.retain(|token| matches!(token, Token::Escape(_)));
+            self.push_word(&[], 0, &space, 0);
+        } else {
+            self.push_word(&space, space_width, &word, word_width);
+        }
+    }
+
+    /// Writes a word and the whitespace before it, breaking the line before
+    /// the word if it does not fit on the current line.
+    fn push_word(
+        &mut self,
+        space: &[Token<'_>],
+        space_width: usize,
+        word: &[Token<'_>],
+        width: usize,
+    ) {
+        if self.line_width > 0 && self.line_width + space_width + width > self.available() {
+            self.break_line(self.options.hanging_indent);
+            // The whitespace is dropped, but its escape sequences apply to
+            // the next line.
+            for token in space {
+                if let Token::Escape(sequence) = token {
+                    self.style = ansi::apply_escape(self.style, sequence);
+                }
+            }
+        } else {
+            for token in space {
+                self.push_token(*token);
+            }
+            self.line_width += space_width;
+        }
+
+        for token in word {
+            let Token::Text(text) = token else {
+                self.push_token(*token);
+                continue;
+            };
+
+            // Only a word that is wider than a whole line is broken.
+            for grapheme in text.graphemes(true) {
+                let width = grapheme_width(grapheme);
+
+                if self.line_width > 0 && self.line_width + width > self.available() {
+                    self.break_line(self.options.hanging_indent);
+                }
+                self.push_token(Token::Text(grapheme));
+                self.line_width += width;
+            }
+        }
+    }
+
+    /// Writes `token`, starting the current line first if needed.
+    fn push_token(&mut self, token: Token<'_>) {
+        if !self.started {
+            self.out.push_str(self.indent);
+            if !self.style.is_plain() {
+                self.out.push_str(&self.style.to_string());
+            }
+            self.started = true;
+        }
+
+        match token {
+            Token::Text(text) => {
+                self.out.push_str(text);
+            }
+            Token::Escape(sequence) => {
+                self.out.push_str(sequence);
+                self.style = ansi::apply_escape(self.style, sequence);
+            }
+        }
+    }
+
+    /// Ends the current line, resetting the active style, and starts a new
+    /// line with `indent`.
+    fn break_line(&mut self, indent: &'a str) {
+        if self.started && !self.style.is_plain() {
+            self.out.push_str("\u{001b}[0m");
+        }
+        self.out.push('\n');
+        self.indent = indent;
+        self.started = false;
+        self.line_width = 0;
+    }
+
+    /// Returns the columns that are available on the current line.
+    fn available(&self) -> usize {
+        self.options.width.saturating_sub(display_width(self.indent))
+    }
+}
+
+/// Returns an iterator over the runs of whitespace and of other characters
+/// in `text`, each with whether it is whitespace. No-break spaces are not
+/// treated as whitespace.
+fn split_spaces(text: &str) -> impl Iterator<Item = (bool, &str)> {
+    let is_space = |c: char| c.is_whitespace() && !matches!(c, '\u{00a0}' | '\u{2007}' | '\u{202f}');
+    let mut rest = text;
+
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let space = is_space(first);
+        let len = rest.find(|c| is_space(c) != space).unwrap_or(rest.len());
+        let (part, tail) = rest.split_at(len);
+        rest = tail;
+        Some((space, part))
+    })
+}
+
+/// Returns `text` with `left` spaces before it and as many spaces after it as
+/// it takes to fill `width` columns.
+fn pad(text: &str, width: usize, left: usize) -> Cow<'_, str> {
+    let text_width = display_width(text);
+
+    if text_width >= width {
+        return Cow::Borrowed(text);
+    }
+
+    let right = width - text_width - left;
+    let mut out = String::with_capacity(text.len() + width - text_width + 4);
+
+    out.extend(std::iter::repeat_n(' ', left));
+    out.push_str(text);
+    if !style_at_end(text).is_plain() {
+        out.push_str("\u{001b}[0m");
+    }
+    out.extend(std::iter::repeat_n(' ', right));
+    Cow::Owned(out)
+}
+
+/// Returns the style that is active at the end of `text`.
+pub fn style_at_end(text: &str) -> Style {
+    ansi::tokens(text).fold(Style::new(), |style, token| match token {
+        Token::Escape(sequence) => ansi::apply_escape(style, sequence),
+        Token::Text(_) => style,
+    })
+}
+
+/// Returns the number of columns that `grapheme` takes up.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.starts_with(char::is_control) {
+        0
+    } else {
+        grapheme.width()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{center, display_width, pad_to, truncate_with_ellipsis, wrap, Wrap};
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("\x1b[1;38;2;255;0;0mred\x1b[0m"), 3);
+        assert_eq!(display_width("日本語 text"), 11);
+        assert_eq!(display_width("\u{1f469}\u{200d}\u{1f52c}!"), 3);
+        assert_eq!(display_width("\u{1f1eb}\u{1f1f7}"), 2);
+        assert_eq!(display_width("a\tb\n"), 2);
+    }
+
+    #[test]
+    fn test_pad_and_center() {
+        assert_eq!(pad_to("abc", 2), "abc");
+        assert_eq!(pad_to("\x1b[44mab", 4), "\x1b[44mab\x1b[0m  ");
+        assert_eq!(pad_to("\x1b[44mab\x1b[49m", 3), "\x1b[44mab\x1b[49m ");
+        assert_eq!(center("ab", 6), "  ab  ");
+        assert_eq!(center("日", 5), " 日  ");
+        assert_eq!(center("abc", 3), "abc");
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        let text = "\x1b[1mbold\x1b[0m \x1b[31mred\x1b[0m";
+
+        assert_eq!(truncate_with_ellipsis(text, 8), text);
+        assert_eq!(truncate_with_ellipsis(text, 7), "\x1b[1mbold\x1b[0m \x1b[31mr…\x1b[0m");
+        assert_eq!(truncate_with_ellipsis(text, 5), "\x1b[1mbold\x1b[0m…");
+        assert_eq!(truncate_with_ellipsis(text, 1), "\x1b[1m…\x1b[0m");
+        assert_eq!(truncate_with_ellipsis(text, 0), "");
+        assert_eq!(truncate_with_ellipsis("日本語", 3), "日…");
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("", 10), "");
+        assert_eq!(wrap("one two three four", 9), "one two\nthree\nfour");
+        assert_eq!(wrap("one   two three", 9), "one   two\nthree");
+        assert_eq!(wrap("  indented text", 10), "  indented\ntext");
+        assert_eq!(wrap("trailing   \nspace", 20), "trailing\nspace");
+        assert_eq!(wrap("abcdefgh ij", 3), "abc\ndef\ngh\nij");
+        assert_eq!(wrap("日本語 テキスト", 6), "日本語\nテキス\nト");
+        assert_eq!(wrap("no\u{a0}break here", 9), "no\u{a0}break\nhere");
+        assert_eq!(wrap("a b", 0), "a\nb");
+    }
+
+    #[test]
+    fn test_wrap_styles() {
+        let text = "\x1b[1mbold \x1b[44mblue\x1b[0m plain \x1b[31mred text\x1b[0m";
+
+        assert_eq!(
+            wrap(text, 9),
+            "\x1b[1mbold \x1b[44mblue\x1b[0m\nplain \x1b[31mred\x1b[0m\n\x1b[31mtext\x1b[0m"
+        );
+        assert_eq!(
+            wrap("\x1b[41mab\ncd\x1b[0m", 10),
+            "\x1b[41mab\x1b[0m\n\x1b[41mcd\x1b[0m"
+        );
+        assert_eq!(wrap("a \x1b[32mbc", 2), "a\n\x1b[32mbc");
+    }
+
+    #[test]
+    fn test_wrap_indents() {
+        let options = Wrap::new(10).indent("* ").hanging_indent("  ");
+
+        assert_eq!(options.apply("one two three four"), "* one two\n  three\n  four");
+        assert_eq!(options.apply("a\n\nb"), "* a\n\n* b");
+        assert_eq!(
+            options.apply("\x1b[44mone two three\x1b[0m"),
+            "* \x1b[44mone two\x1b[0m\n  \x1b[44mthree\x1b[0m"
+        );
+    }
+}
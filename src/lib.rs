@@ -14,8 +14,87 @@
 //! * `write_styled`/`write_color256`/`write_rgb` write colored text to a buffer.
 //! * `print_styled`/`print_color256`/`print_rgb` print colored text to stdout.
 //! * `eprint_styled`/`eprint_color256`/`eprint_rgb` print colored text to stderr.
+//! * `write_gradient`/`print_gradient`/`eprint_gradient` write text with a color
+//!   gradient.
+//! * `write_markup`/`print_markup`/`eprint_markup` write text styled with inline
+//!   `<style>...</>` tags.
+//! * `strip_ansi`/`StripAnsiWriter` remove the escape sequences from styled text,
+//!   e.g. before writing it to a log file.
+//! * `parse_ansi` parses styled text back into spans of text and their styles,
+//!   e.g. to compare output in tests.
+//! * `display_width`/`pad_to`/`center`/`truncate_with_ellipsis` lay out styled text
+//!   by its visible width, e.g. in table cells.
+//! * `wrap`/`Wrap` wrap styled text at word boundaries without letting styles bleed
+//!   into the next line.
+//! * `ansi_to_html`/`Html`/`HtmlWriter` convert styled text into HTML, e.g. to
+//!   publish colored output on a web page.
+//! * `ansi_to_svg`/`Svg` render styled text as an SVG image of a terminal window,
+//!   e.g. for screenshots in a README.
 //! 
 //! Note that each macro has a newline version (e.g. `println_styled`).
+//!
+//! Errors
+//! ------
+//!
+//! The `write_*` macros panic if writing to the buffer fails. The `print_*` and
+//! `eprint_*` macros exit the process quietly with status 0 on `BrokenPipe`
+//! errors, which occur when the output is piped into a program like `head` that
+//! exits early, and panic on any other error.
+//!
+//! Each macro also has a fallible `try_` version (e.g. `try_print_styled`,
+//! `try_writeln_rgb`) that returns the `std::io::Result<()>` (or
+//! `std::fmt::Result`) of the write instead of panicking.
+//!
+//! The text argument of every macro is a format string followed by its
+//! arguments, exactly like `println!` (e.g.
+//! `println_styled!("red", "x = {} ({:>8})", x, y)`). A single non-literal
+//! expression implementing `Display` is also accepted as the text.
+//!
+//! Disabling Colors
+//! ----------------
+//!
+//! The `print_*` and `eprint_*` macros print plain text without any escape codes
+//! when the environment asks for it. The following environment variables are
+//! consulted in this order, and the first one that applies decides:
+//!
+//! 1. `NO_COLOR` set to a non-empty value disables colors.
+//! 2. `CLICOLOR_FORCE` set to a non-empty value other than "0" enables colors.
+//! 3. `CLICOLOR` set to "0" disables colors.
+//! 4. `TERM` set to "dumb" disables colors.
+//!
+//! When none of them applies, colors are enabled only if the stream is a
+//! terminal. The decision is made separately for stdout and stderr, so piping
+//! stdout into a file prints plain text while `eprint_*` output to a terminal
+//! stays colored. It is made once per process and can be overridden with
+//! `set_stream_colors`. The `write_*` macros write escape codes regardless of
+//! the environment.
+//!
+//! A program with a `--color=auto|always|never` flag passes it on with
+//! `set_color_choice`, which takes precedence over the environment and
+//! applies to the `write_*` macros and `Styled` values as well.
+//! `scoped_color_choice` overrides the color choice on the current thread
+//! until the returned guard is dropped (e.g. to force plain output in a test):
+//!
+//! ```rust
+//! use color_macros::{scoped_color_choice, ColorChoice, Colorize};
+//!
+//! let _guard = scoped_color_choice(ColorChoice::Never);
+//!
+//! assert_eq!(format!("{}", "done".green()), "done");
+//! ```
+//!
+//! Color Depth
+//! -----------
+//!
+//! Terminals that cannot display 24-bit RGB or 256-color mode colors (e.g. the
+//! Linux console) print garbage or nothing for them, so the `print_*` and
+//! `eprint_*` macros and `Fg::get_ansi_code`/`Bg::get_ansi_code` downgrade
+//! colors to the closest color the terminal supports: 24-bit RGB → 256-color
+//! mode → the 16 basic and bright colors → no colors. The color depth is
+//! detected once per process from `COLORTERM`, `TERM`, `TERM_PROGRAM` and
+//! `WT_SESSION` (see `env_color_depth`) and can be overridden with
+//! `set_color_depth`. The `write_*` macros and `Gradient::apply` never downgrade
+//! colors, since their output is not necessarily written to the terminal.
 //! 
 //! Usage
 //! =====
@@ -33,17 +112,179 @@
 //! Entering "X" alone (e.g. "red") for the style string sets the foreground color
 //! without altering the background, while entering "on Y" (e.g. "on red") for the
 //! style string sets the background color without altering the foreground.
+//!
+//! Instead of a color name, X and Y may also be a 24-bit RGB color written as
+//! `#ff8800`, `#f80` or `rgb(255, 136, 0)`, or a 256-color mode color number
+//! written as `208`, `256:208` or `color(208)`. This lets a single style string
+//! mix the basic, 256-color and RGB color modes (e.g. "bold #ff8800 on 236").
+//!
+//! Text attributes can be combined with any of the colors by adding one or more
+//! of the following words to the style string: bold, dim, italic, underline,
+//! blink, reverse, hidden, or strikethrough (e.g. "bold underline bright yellow
+//! on blue" or just "italic"). The `Attribute` enum and `Attributes` set expose
+//! the same attributes to programmatic users of `Style`.
+//!
+//! A style string literal is parsed and validated at compile time, so a typo
+//! such as "brigth red" is a compile error rather than silently producing no
+//! color. Styles built at runtime (e.g. a `String`) are parsed at runtime.
+//!
+//! Style strings that are only known at runtime (e.g. from a config file) can be
+//! validated ahead of time by parsing them into a `Style` (or a single color name
+//! into a `Color`). A `ParseStyleError` reports the offending token, its
+//! position, and the closest valid color name:
+//!
+//! ```rust
+//! use color_macros::Style;
+//!
+//! let err = "bright purpel on white".parse::<Style>().unwrap_err();
+//!
+//! assert_eq!(
+//!     err.to_string(),
+//!     "unknown color or attribute name \"purpel\" at position 7; did you mean \"magenta\"?"
+//! );
+//! ```
+//!
+//! Instead of a style string, every `x_styled!()` macro also accepts a `Style`
+//! built with its builder methods. Displaying a `Style` writes the escape
+//! sequence that turns it on:
+//!
+//! ```rust
+//! use color_macros::{write_styled, Color, Style};
+//!
+//! let warning = Style::new().fg(Color::Yellow).on(Color::Rgb(40, 40, 40)).bold();
+//! let mut buffer: Vec<u8> = vec![];
+//!
+//! write_styled!(&mut buffer, warning, "careful");
+//!
+//! assert_eq!(buffer.as_slice(), b"\x1b[1;33;48;2;40;40;40mcareful\x1b[0m");
+//! assert_eq!(warning.to_string(), "\x1b[1;33;48;2;40;40;40m");
+//! ```
+//!
+//! Colored fragments inside larger messages (e.g. for `format!` or a logger) are
+//! created with the `Colorize` extension trait, whose methods wrap any `Display`
+//! value in a `Styled` value. Width, precision and alignment flags apply to the
+//! wrapped value:
+//!
+//! ```rust
+//! use color_macros::Colorize;
+//!
+//! let message = format!("[{:>6}] {}", "done".green().bold(), 42.on_color256(236));
+//!
+//! assert_eq!(message, "[\x1b[1;32m  done\x1b[0m] \x1b[48;5;236m42\x1b[0m");
+//! ```
+//!
+//! Instead of hard-coding colors, a style string can name a style of the current
+//! `Theme` (e.g. `eprintln_styled!("error", "{}", msg)`). A single-word style string
+//! is looked up in the theme before the built-in color names, so a style can be
+//! changed in one place with `set_theme`. Since theme names are only known at
+//! runtime, no single-word name is a compile error, not even one that is close to a
+//! built-in name (e.g. "ok" or a typo such as "purpel"). The text of a word that is
+//! neither in the theme nor a built-in name is written without escape codes. The
+//! default theme defines error, warning, info, success, debug and hint:
+//!
+//! ```rust
+//! use color_macros::{set_theme, theme, write_styled, Color, Style};
+//!
+//! set_theme(theme().with("warning", Style::new().fg(Color::Rgb(255, 136, 0))));
+//!
+//! let mut buffer: Vec<u8> = vec![];
+//! write_styled!(&mut buffer, "warning", "careful");
+//!
+//! assert_eq!(buffer.as_slice(), b"\x1b[38;2;255;136;0mcareful\x1b[0m");
+//! ```
+//!
+//! With the `toml` or `json` cargo feature enabled, a theme can be loaded from a
+//! file that maps names to style strings (e.g. `error = "bold bright red"` in a
+//! `.toml` file), so styles can be changed without recompiling. Every invalid
+//! entry is reported with the file, its key and the offending token:
+//!
+//! ```rust,ignore
+//! use color_macros::{set_theme, Theme};
+//!
+//! let mut theme = Theme::default();
+//! theme.extend(Theme::load("theme.toml")?.iter());
+//! set_theme(theme);
+//! ```
 //! 
 //! 256-Color Mode
 //! --------------
 //! 
 //! Foreground and background colors are set using color numbers ranging from 0 - 255.
+//! Pass `_` in place of either color number to leave that color unchanged.
 //! 
 //! 24-Bit RGB Color Mode
 //! ---------------------
 //! 
 //! Foreground and background colors are set using tuples containing red, green, and
-//! blue color values, each ranging from 0 - 255.
+//! blue color values, each ranging from 0 - 255. Pass `_` in place of either tuple
+//! to leave that color unchanged.
+//!
+//! Instead of hand-computing RGB values, a `Color::Rgb` can be built from its hue,
+//! saturation and lightness (`Color::from_hsl`), hue, saturation and value
+//! (`Color::from_hsv`), or CIE Lab or Oklab components (`Color::from_lab`,
+//! `Color::from_oklab`), and converted back with `to_hsl`, `to_hsv`, `to_lab`
+//! and `to_oklab`:
+//!
+//! ```rust
+//! use color_macros::Color;
+//!
+//! let orange = Color::from_hsl(30.0, 1.0, 0.5);
+//!
+//! assert_eq!(orange, Color::Rgb(255, 128, 0));
+//! assert_eq!(orange.to_hsv().map(|(h, _, _)| h.round()), Some(30.0));
+//! ```
+//!
+//! Gradients
+//! ---------
+//!
+//! The `*_gradient` macros color each grapheme of the text with the color between
+//! two RGB color stops at its position, and write the whole text at once. Pass a
+//! `Gradient` in place of both tuples to use more stops, interpolate in Oklab
+//! instead of RGB (`Interpolation::Oklab`) or color the background instead of the
+//! foreground (`Gradient::background`):
+//!
+//! * `write_gradient!(buffer, (r1, g1, b1), (r2, g2, b2), fmt, args...)`
+//! * `print_gradient!((r1, g1, b1), (r2, g2, b2), fmt, args...)`
+//! * `eprint_gradient!(gradient, fmt, args...)`
+//!
+//! ```rust
+//! use color_macros::{println_gradient, Color, Gradient, Interpolation};
+//!
+//! // Print text fading from red to blue to stdout.
+//! println_gradient!((255, 0, 0), (0, 0, 255), "test");
+//!
+//! // Print text on a background fading from red through green to blue.
+//! let gradient = Gradient::new([Color::Red, Color::Green, Color::Blue])
+//!     .interpolation(Interpolation::Oklab)
+//!     .background();
+//! println_gradient!(gradient, "test");
+//!
+//! assert_eq!(gradient.color_at(0.0), Some(Color::Rgb(205, 0, 0)));
+//! ```
+//!
+//! Markup
+//! ------
+//!
+//! The `*_markup` macros style parts of a single format string with tags: `<style>`
+//! turns on any style string or theme name until the matching `</>`, tags can be
+//! nested, and `<<` writes a literal `<`. Tags in the format string are checked at
+//! compile time. Tags in the arguments are rendered leniently (an invalid tag is
+//! written as text), so pass untrusted text through `escape_markup`:
+//!
+//! * `write_markup!(buffer, fmt, args...)`
+//! * `print_markup!(fmt, args...)`
+//! * `eprint_markup!(fmt, args...)`
+//!
+//! ```rust
+//! use color_macros::{eprintln_markup, escape_markup, write_markup};
+//!
+//! let path = "Vec<u8>.rs";
+//! eprintln_markup!("<error>error</>: file <cyan>{}</> not found", escape_markup(path));
+//!
+//! let mut buffer: Vec<u8> = vec![];
+//! write_markup!(&mut buffer, "<bold>a <red>b</></>");
+//! assert_eq!(buffer.as_slice(), b"\x1b[1ma \x1b[0m\x1b[1;31mb\x1b[0m");
+//! ```
 //!
 
 #![deny(clippy::all)]
@@ -56,263 +297,104 @@
 #![deny(clippy::style)]
 #![deny(clippy::suspicious)]
 #![deny(missing_docs)]
+#![cfg_attr(test, allow(clippy::needless_range_loop, clippy::op_ref))]
 
-/// Color options
-#[derive(PartialEq, Eq)]
-pub enum Color {
-    /// Black
-    Black,
-    /// Red
-    Red,
-    /// Green
-    Green,
-    /// Yellow
-    Yellow,
-    /// Blue
-    Blue,
-    /// Magenta
-    Magenta,
-    /// Cyan
-    Cyan,
-    /// White
-    White,
-    /// 256-color mode colors
-    Color256(u8),
-    /// 24-bit RGB colors
-    Rgb(u8, u8, u8),
-    /// Current color value
-    Current,
-    /// Reset color attributes
-    Reset,
-}
-
-impl Color {
-    /// Returns true if this `Color` instance is `Color::Current`.
-    #[must_use]
-    pub fn is_current(&self) -> bool {
-        *self == Self::Current
-    }
+mod ansi;
+mod attribute;
+mod color;
+mod color_space;
+mod control;
+mod gradient;
+mod html;
+mod layout;
+mod markup;
+#[doc(hidden)]
+pub mod parse;
+#[doc(hidden)]
+pub mod stream;
+mod style;
+mod styled;
+mod svg;
+mod theme;
+#[cfg(any(feature = "toml", feature = "json"))]
+mod theme_file;
 
-    /// Returns a semicolon separator if self and other are not `Current`.
-    /// Else it returns an empty string.
-    #[must_use]
-    pub fn get_separator(&self, other_is_current: bool) -> String {
-        if !self.is_current() && !other_is_current {
-            ";".to_string()
-        } else {
-            String::new()
-        }
-    }
-}
-
-impl From<&str> for Color {
-    fn from(value: &str) -> Self {
-        match value {
-            "black" => Self::Black,
-            "red" => Self::Red,
-            "green" => Self::Green,
-            "yellow" => Self::Yellow,
-            "blue" => Self::Blue,
-            "magenta" => Self::Magenta,
-            "cyan" => Self::Cyan,
-            "white" => Self::White,
-            "reset" => Self::Reset,
-            _ => Self::Current,
-        }
-    }
-}
-
-/// Wrapper that represents a regular foreground color.
-pub struct Fg;
-
-impl Fg {
-    /// Returns the numeric portion of the foreground ANSI color code.
-    #[must_use]
-    pub fn get_ansi_code(color: &Color) -> String {
-        match *color {
-            Color::Black => "30".to_string(),
-            Color::Red => "31".to_string(),
-            Color::Green => "32".to_string(),
-            Color::Yellow => "33".to_string(),
-            Color::Blue => "34".to_string(),
-            Color::Magenta => "35".to_string(),
-            Color::Cyan => "36".to_string(),
-            Color::White => "37".to_string(),
-            Color::Reset => "0".to_string(),
-            Color::Color256(num) => format!("38;5;{num}"),
-            Color::Rgb(r, g, b) => format!("38;2;{r};{g};{b}"),
-            Color::Current => String::new(),
-        }
-    }
-}
-
-/// Wrapper that represents a bright foreground color.
-pub struct FgBright;
-
-impl FgBright {
-    /// Returns the numeric portion of the foreground ANSI color code.
-    #[must_use]
-    pub fn get_ansi_code(color: &Color) -> String {
-        match *color {
-            Color::Black => "90".to_string(),
-            Color::Red => "91".to_string(),
-            Color::Green => "92".to_string(),
-            Color::Yellow => "93".to_string(),
-            Color::Blue => "94".to_string(),
-            Color::Magenta => "95".to_string(),
-            Color::Cyan => "96".to_string(),
-            Color::White => "97".to_string(),
-            Color::Reset => "0".to_string(),
-            Color::Color256(num) => format!("38;5;{num}"),
-            Color::Rgb(r, g, b) => format!("38;2;{r};{g};{b}"),
-            Color::Current => String::new(),
-        }
-    }
-}
-
-/// Wrapper that represents a regular background color.
-pub struct Bg;
-
-impl Bg {
-    /// Returns the numeric portion of the background ANSI color code.
-    #[must_use]
-    pub fn get_ansi_code(color: &Color) -> String {
-        match *color {
-            Color::Black => "40".to_string(),
-            Color::Red => "41".to_string(),
-            Color::Green => "42".to_string(),
-            Color::Yellow => "43".to_string(),
-            Color::Blue => "44".to_string(),
-            Color::Magenta => "45".to_string(),
-            Color::Cyan => "46".to_string(),
-            Color::White => "47".to_string(),
-            Color::Reset => "0".to_string(),
-            Color::Color256(num) => format!("48;5;{num}"),
-            Color::Rgb(r, g, b) => format!("48;2;{r};{g};{b}"),
-            Color::Current => String::new(),
-        }
-    }
-}
-
-/// Wrapper that represents a bright background color.
-pub struct BgBright;
-
-impl BgBright {
-    /// Returns the numeric portion of the foreground ANSI color code.
-    #[must_use]
-    pub fn get_ansi_code(color: &Color) -> String {
-        match *color {
-            Color::Black => "100".to_string(),
-            Color::Red => "101".to_string(),
-            Color::Green => "102".to_string(),
-            Color::Yellow => "103".to_string(),
-            Color::Blue => "104".to_string(),
-            Color::Magenta => "105".to_string(),
-            Color::Cyan => "106".to_string(),
-            Color::White => "107".to_string(),
-            Color::Reset => "0".to_string(),
-            Color::Color256(num) => format!("48;5;{num}"),
-            Color::Rgb(r, g, b) => format!("48;2;{r};{g};{b}"),
-            Color::Current => String::new(),
-        }
-    }
-}
+pub use ansi::{parse_ansi, strip_ansi, StripAnsiWriter};
+pub use attribute::{Attribute, Attributes};
+pub use color::{Bg, BgBright, Color, Fg, FgBright, Palette};
+pub use control::{
+    color_choice, color_depth, env_color_depth, scoped_color_choice, set_color_choice,
+    set_color_depth, set_stream_colors, stream_colors, ColorChoice, ColorChoiceGuard, ColorDepth,
+};
+pub use gradient::{Gradient, GradientText, Interpolation};
+pub use html::{ansi_to_html, Html, HtmlWriter};
+pub use layout::{center, display_width, pad_to, truncate_with_ellipsis, wrap, Wrap};
+pub use markup::{escape_markup, parse_markup, MarkupError, MarkupErrorKind};
+pub use style::{ParseStyleError, ParseStyleErrorKind, Style};
+pub use stream::Stream;
+pub use styled::{Colorize, Styled};
+pub use svg::{ansi_to_svg, Svg};
+pub use theme::{set_theme, theme, Theme};
+#[cfg(any(feature = "toml", feature = "json"))]
+pub use theme_file::{LoadThemeError, LoadThemeErrorKind, ThemeEntryError, ThemeEntryErrorKind};
 
 /// Parses the style expression in `x_styled!()` macros and returns a string
 /// containing the ansi color code(s).
+///
+/// A string literal style is parsed at compile time and the color code(s) are
+/// returned as a `Cow<'static, str>` borrowing a static string. A single word
+/// (e.g. "error" or "red") is first looked up in the current `Theme` at
+/// runtime, and any single word (e.g. "ok", which is close to "on") is not a
+/// compile error since it may name a style of the theme. Any
+/// other style expression (e.g. a `String` or a `Style`) is converted at
+/// runtime and the color code(s) are returned as a `String`. An invalid
+/// runtime style string or an unknown theme name has no color codes, and the
+/// macros write the text without escape codes (use `Style`'s `FromStr`
+/// implementation to validate it).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::parse_colors;
+///
+/// assert_eq!(parse_colors!("bright red on blue"), "91;44m");
+/// assert_eq!(parse_colors!("error"), "1;31m");
+///
+/// let style = String::from("bright red on blue");
+/// assert_eq!(parse_colors!(style), "91;44m");
+///
+/// let style = color_macros::Style::new().on(color_macros::Color::Blue);
+/// assert_eq!(parse_colors!(style), "44m");
+/// ```
+///
+/// An invalid literal style string is a compile error:
+///
+/// ```rust,compile_fail
+/// use color_macros::parse_colors;
+///
+/// // error: invalid style string "brigth red": unknown color or attribute
+/// // name "brigth" at position 0; did you mean "bright"?
+/// let codes = parse_colors!("brigth red");
+/// ```
 #[macro_export]
 macro_rules! parse_colors {
-    ($style:expr) => {{
-        use $crate::{Color, Bg, Fg, BgBright, FgBright};
-
-        let style_args: Vec<String> = $style.split(' ')
-            .map(|s| {
-                let s = s.trim();
-                let mut new_str = s.to_string();
-                new_str.make_ascii_lowercase();
-                new_str
-            })
-            .collect();
-
-        let style_strs: Vec<&str> = style_args.iter()
-            .map(|s| s.as_str())
-            .collect();
-
-        let mut ansi_code = String::new();
-
-        match style_strs.len() {
-            // Regular foreground color (e.g. "red")
-            1 => {
-                ansi_code.push_str(&Fg::get_ansi_code(&Color::from(style_strs[0])));
-                ansi_code.push('m');
-            },
-            2 => match style_strs[..] {
-                // Regular background color (e.g. "on red")
-                ["on", bg_color] => {
-                    ansi_code.push_str(&Bg::get_ansi_code(&Color::from(bg_color)));
-                    ansi_code.push('m');
-                },
-                // Bright foreground color (e.g. "bright red")
-                ["bright", fg_br_color] => {
-                    ansi_code.push_str(&FgBright::get_ansi_code(&Color::from(fg_br_color)));
-                    ansi_code.push('m');
-                },
-                _ => {},
-            },
-            3 => match style_strs[..] {
-                // Bright background color (e.g. "on bright red")
-                ["on", "bright", bg_br_color] => {
-                    ansi_code.push_str(&BgBright::get_ansi_code(&Color::from(bg_br_color)));
-                    ansi_code.push('m');
-                },
-                // Regular foreground color and regular background color (e.g. "green on red")
-                [fg_color, "on", bg_color] => {
-                    ansi_code.push_str(&Fg::get_ansi_code(&Color::from(fg_color)));
-                    if fg_color != "current" && bg_color != "current" { ansi_code.push(';') }
-                    ansi_code.push_str(&Bg::get_ansi_code(&Color::from(bg_color)));
-                    ansi_code.push('m');
-                },
-                _ => {},
-            },
-            4 => match style_strs[..] {
-                // Regular foreground color and bright background color (e.g. "green on bright red")
-                [fg_color, "on", "bright", bg_br_color] => {
-                    ansi_code.push_str(&Fg::get_ansi_code(&Color::from(fg_color)));
-                    if fg_color != "current" && bg_br_color != "current" { ansi_code.push(';') }
-                    ansi_code.push_str(&BgBright::get_ansi_code(&Color::from(bg_br_color)));
-                    ansi_code.push('m');
-                },
-                // Bright foreground color and regular background color (e.g. "bright green on red")
-                ["bright", fg_br_color, "on", bg_color] => {
-                    ansi_code.push_str(&FgBright::get_ansi_code(&Color::from(fg_br_color)));
-                    if fg_br_color != "current" && bg_color != "current" { ansi_code.push(';') }
-                    ansi_code.push_str(&Bg::get_ansi_code(&Color::from(bg_color)));
-                    ansi_code.push('m');
-                },
-                _ => {},
-            },
-            5 => match style_strs[..] {
-                // Bright foreground color and bright background color (e.g. "bright green on bright red")
-                ["bright", fg_br_color, "on", "bright", bg_br_color] => {
-                    ansi_code.push_str(&FgBright::get_ansi_code(&Color::from(fg_br_color)));
-                    if fg_br_color != "current" && bg_br_color != "current" { ansi_code.push(';') }
-                    ansi_code.push_str(&BgBright::get_ansi_code(&Color::from(bg_br_color)));
-                    ansi_code.push('m');
-                },
-                _ => {},
-            },
-            _ => {},
-        }
-        ansi_code
+    ($style:literal) => {{
+        static CODES: $crate::parse::Codes = $crate::parse::literal_codes($style);
+        $crate::parse::themed_codes($style, CODES.as_str())
     }};
+    ($style:expr) => {
+        $crate::parse::runtime_codes(&$style)
+    };
 }
 
 /// Write color text to a buffer.
 ///
 /// # Arguments
 /// * `$buffer` - The destination buffer.
-/// * `$style` - "X on Y" style string (e.g. "bright green on white".
-/// * `$text` - The text to write.
+/// * `$style` - "X on Y" style string (e.g. "bright green on white") or a
+///   `Style`.
+/// * `$fmt`, `$args` - The text to write, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -326,41 +408,98 @@ macro_rules! parse_colors {
 /// write_styled!(&mut buffer, "current on bright magenta", "test");
 ///
 /// assert_eq!(buffer.as_slice(), b"\x1b[105mtest\x1b[0m");
+///
+/// // Format arguments are forwarded to `format_args!()`.
+/// buffer.clear();
+/// write_styled!(&mut buffer, "red", "x = {} ({:>3})", 1, 2);
+///
+/// assert_eq!(buffer.as_slice(), b"\x1b[31mx = 1 (  2)\x1b[0m");
 /// ```
 #[macro_export]
 macro_rules! write_styled {
-    ($buffer:expr, $style:expr, $text:expr) => {{
+    ($($arg:tt)+) => {
+        $crate::try_write_styled!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `write_styled!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::try_write_styled;
+///
+/// let mut buffer: Vec<u8> = vec![];
+///
+/// try_write_styled!(&mut buffer, "red", "test")?;
+///
+/// assert_eq!(buffer.as_slice(), b"\x1b[31mtest\x1b[0m");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[macro_export]
+macro_rules! try_write_styled {
+    (@codes $buffer:expr, $codes:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
         write!(
             $buffer,
-            "\u{001b}[{}{}\u{001b}[0m",
-            $crate::parse_colors!($style),
-            $text
+            "{}",
+            $crate::stream::write_args(
+                &$codes,
+                format_args!($fmt $($arg)*)
+            )
         )
-        .unwrap();
     }};
+    (@codes $buffer:expr, $codes:expr, $text:expr) => {
+        $crate::try_write_styled!(@codes $buffer, $codes, "{}", $text)
+    };
+    ($buffer:expr, $style:expr, $($arg:tt)+) => {
+        $crate::try_write_styled!(@codes $buffer, $crate::parse_colors!($style), $($arg)+)
+    };
 }
 
 /// Same as `write_styled!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! writeln_styled {
-    ($buffer:expr, $style:expr, $text:expr) => {{
+    ($($arg:tt)+) => {
+        $crate::try_writeln_styled!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `writeln_styled!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_writeln_styled {
+    (@codes $buffer:expr, $codes:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
         write!(
             $buffer,
-            "\u{001b}[{}{}\u{001b}[0m\n",
-            $crate::parse_colors!($style),
-            $text
+            "{}\n",
+            $crate::stream::write_args(
+                &$codes,
+                format_args!($fmt $($arg)*)
+            )
         )
-        .unwrap();
     }};
+    (@codes $buffer:expr, $codes:expr, $text:expr) => {
+        $crate::try_writeln_styled!(@codes $buffer, $codes, "{}", $text)
+    };
+    ($buffer:expr, $style:expr, $($arg:tt)+) => {
+        $crate::try_writeln_styled!(@codes $buffer, $crate::parse_colors!($style), $($arg)+)
+    };
 }
 
 /// Print color text to stdout.
 ///
 /// # Arguments
-/// * `$style` - "X on Y" style string (e.g. "bright green on white".
-/// * `$text` - The text to print to stdout.
+/// * `$style` - "X on Y" style string (e.g. "bright green on white") or a
+///   `Style`.
+/// * `$fmt`, `$args` - The text to print to stdout, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -369,45 +508,88 @@ macro_rules! writeln_styled {
 ///
 /// // Prints bright green text on a black background to stdout.
 /// print_styled!("bright green on black", "test");
+///
+/// // Prints formatted text without allocating an intermediate `String`.
+/// let count = 3;
+/// print_styled!("bright green", "{count} tests passed");
 /// ```
 #[macro_export]
 macro_rules! print_styled {
-    ($style:expr, $text:expr) => {{
-        use std::io::Write;
-        let mut stdout = std::io::stdout().lock();
-        write!(
-            &mut stdout,
-            "\u{001b}[{}{}\u{001b}[0m",
-            $crate::parse_colors!($style),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_print_styled!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `print_styled!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::try_print_styled;
+///
+/// fn main() -> std::io::Result<()> {
+///     for i in 0.. {
+///         // Stops with an error once stdout is closed, e.g. by `head`.
+///         try_print_styled!("green", "line {i}\n")?;
+///         # break;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! try_print_styled {
+    (@codes $codes:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stdout,
+            &$codes,
+            format_args!($fmt $($arg)*),
+            false,
         )
-        .unwrap();
-        stdout.flush().unwrap();
-    }};
+    };
+    (@codes $codes:expr, $text:expr) => {
+        $crate::try_print_styled!(@codes $codes, "{}", $text)
+    };
+    ($style:expr, $($arg:tt)+) => {
+        $crate::try_print_styled!(@codes $crate::parse_colors!($style), $($arg)+)
+    };
 }
 
 /// Same as `print_styled!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! println_styled {
-    ($style:expr, $text:expr) => {{
-        use std::io::Write;
-        let mut stdout = std::io::stdout().lock();
-        write!(
-            &mut stdout,
-            "\u{001b}[{}{}\u{001b}[0m\n",
-            $crate::parse_colors!($style),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_println_styled!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `println_styled!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_println_styled {
+    (@codes $codes:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stdout,
+            &$codes,
+            format_args!($fmt $($arg)*),
+            true,
         )
-        .unwrap();
-        stdout.flush().unwrap();
-    }};
+    };
+    (@codes $codes:expr, $text:expr) => {
+        $crate::try_println_styled!(@codes $codes, "{}", $text)
+    };
+    ($style:expr, $($arg:tt)+) => {
+        $crate::try_println_styled!(@codes $crate::parse_colors!($style), $($arg)+)
+    };
 }
 
 /// Print color text to stderr.
 ///
 /// # Arguments
-/// * `$style` - "X on Y" style string (e.g. "bright green on white".
-/// * `$text` - The text to print to stderr.
+/// * `$style` - "X on Y" style string (e.g. "bright green on white") or a
+///   `Style`.
+/// * `$fmt`, `$args` - The text to print to stderr, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -416,47 +598,75 @@ macro_rules! println_styled {
 ///
 /// // Prints bright red text on a white background to stderr.
 /// eprint_styled!("bright red on white", "test");
+///
+/// // Prints orange RGB text on a dark gray 256-color mode background.
+/// eprint_styled!("#ff8800 on 236", "test");
 /// ```
 #[macro_export]
 macro_rules! eprint_styled {
-    ($style:expr, $text:expr) => {{
-        use std::io::Write;
-        let mut stderr = std::io::stderr().lock();
-        write!(
-            &mut stderr,
-            "\u{001b}[{}{}\u{001b}[0m",
-            $crate::parse_colors!($style),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprint_styled!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprint_styled!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprint_styled {
+    (@codes $codes:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stderr,
+            &$codes,
+            format_args!($fmt $($arg)*),
+            false,
         )
-        .unwrap();
-        stderr.flush().unwrap();
-    }};
+    };
+    (@codes $codes:expr, $text:expr) => {
+        $crate::try_eprint_styled!(@codes $codes, "{}", $text)
+    };
+    ($style:expr, $($arg:tt)+) => {
+        $crate::try_eprint_styled!(@codes $crate::parse_colors!($style), $($arg)+)
+    };
 }
 
 /// Same as `eprint_styled!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! eprintln_styled {
-    ($style:expr, $text:expr) => {{
-        use std::io::Write;
-        let mut stderr = std::io::stderr().lock();
-        write!(
-            &mut stderr,
-            "\u{001b}[{}{}\u{001b}[0m\n",
-            $crate::parse_colors!($style),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprintln_styled!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprintln_styled!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprintln_styled {
+    (@codes $codes:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stderr,
+            &$codes,
+            format_args!($fmt $($arg)*),
+            true,
         )
-        .unwrap();
-        stderr.flush().unwrap();
-    }};
+    };
+    (@codes $codes:expr, $text:expr) => {
+        $crate::try_eprintln_styled!(@codes $codes, "{}", $text)
+    };
+    ($style:expr, $($arg:tt)+) => {
+        $crate::try_eprintln_styled!(@codes $crate::parse_colors!($style), $($arg)+)
+    };
 }
 
 /// Write color text to a buffer using 256-color mode.
 ///
 /// # Arguments
 /// * `$buffer` - The destination buffer.
-/// * `$fg_num` - The foreground 256-color mode color number.
-/// * `$bg_num` - The background 256-color mode color number.
-/// * `$text` - The text to write.
+/// * `$fg_num` - The foreground 256-color mode color number, or `_` to leave
+///   the foreground color unchanged.
+/// * `$bg_num` - The background 256-color mode color number, or `_` to leave
+///   the background color unchanged.
+/// * `$fmt`, `$args` - The text to write, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -469,48 +679,101 @@ macro_rules! eprintln_styled {
 /// write_color256!(&mut buffer, 196, 255, "test");
 ///
 /// assert_eq!(buffer.as_slice(), b"\x1b[38;5;196;48;5;255mtest\x1b[0m");
+///
+/// // Write red text on the current background color to a buffer.
+/// buffer.clear();
+/// write_color256!(&mut buffer, 196, _, "test");
+///
+/// assert_eq!(buffer.as_slice(), b"\x1b[38;5;196mtest\x1b[0m");
 /// ```
 #[macro_export]
 macro_rules! write_color256 {
-    ($buffer:expr, $fg_num:expr, $bg_num:expr, $text:expr) => {{
+    ($($arg:tt)+) => {
+        $crate::try_write_color256!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `write_color256!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_write_color256 {
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
-        use $crate::{Bg, Color, Fg};
         write!(
             $buffer,
-            "\u{001b}[{};{}m{}\u{001b}[0m",
-            Fg::get_ansi_code(&Color::Color256($fg_num)),
-            Bg::get_ansi_code(&Color::Color256($bg_num)),
-            $text
+            "{}",
+            $crate::stream::write_args(
+                $crate::parse::color_codes($fg, $bg).as_str(),
+                format_args!($fmt $($arg)*)
+            )
         )
-        .unwrap();
-        $buffer.flush().unwrap();
     }};
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_write_color256!(@colors $buffer, $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    ($buffer:expr, _, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_write_color256!(@colors $buffer, None, Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
+    ($buffer:expr, $fg_num:expr, _, $($arg:tt)+) => {
+        $crate::try_write_color256!(@colors $buffer, Some($crate::Color::Color256($fg_num)), None, $($arg)+)
+    };
+    ($buffer:expr, $fg_num:expr, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_write_color256!(@colors $buffer, Some($crate::Color::Color256($fg_num)), Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
 }
 
 /// Same as `write_color256!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! writeln_color256 {
-    ($buffer:expr, $fg_num:expr, $bg_num:expr, $text:expr) => {{
+    ($($arg:tt)+) => {
+        $crate::try_writeln_color256!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `writeln_color256!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_writeln_color256 {
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
-        use $crate::{Bg, Color, Fg};
         write!(
             $buffer,
-            "\u{001b}[{};{}m{}\u{001b}[0m\n",
-            Fg::get_ansi_code(&Color::Color256($fg_num)),
-            Bg::get_ansi_code(&Color::Color256($bg_num)),
-            $text
+            "{}\n",
+            $crate::stream::write_args(
+                $crate::parse::color_codes($fg, $bg).as_str(),
+                format_args!($fmt $($arg)*)
+            )
         )
-        .unwrap();
-        $buffer.flush().unwrap();
     }};
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_writeln_color256!(@colors $buffer, $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    ($buffer:expr, _, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_writeln_color256!(@colors $buffer, None, Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
+    ($buffer:expr, $fg_num:expr, _, $($arg:tt)+) => {
+        $crate::try_writeln_color256!(@colors $buffer, Some($crate::Color::Color256($fg_num)), None, $($arg)+)
+    };
+    ($buffer:expr, $fg_num:expr, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_writeln_color256!(@colors $buffer, Some($crate::Color::Color256($fg_num)), Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
 }
 
 /// Print color text to stdout.
 ///
 /// # Arguments
-/// * `$fg_num` - The foreground 256-color mode color number.
-/// * `$bg_num` - The background 256-color mode color number.
-/// * `$text` - The text to print to stdout.
+/// * `$fg_num` - The foreground 256-color mode color number, or `_` to leave
+///   the foreground color unchanged.
+/// * `$bg_num` - The background 256-color mode color number, or `_` to leave
+///   the background color unchanged.
+/// * `$fmt`, `$args` - The text to print to stdout, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -522,47 +785,82 @@ macro_rules! writeln_color256 {
 /// ```
 #[macro_export]
 macro_rules! print_color256 {
-    ($fg_num:expr, $bg_num:expr, $text:expr) => {{
-        use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stdout = std::io::stdout().lock();
-        write!(
-            &mut stdout,
-            "\u{001b}[{};{}m{}\u{001b}[0m",
-            Fg::get_ansi_code(&Color::Color256($fg_num)),
-            Bg::get_ansi_code(&Color::Color256($bg_num)),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_print_color256!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `print_color256!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_print_color256 {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stdout,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            false,
         )
-        .unwrap();
-        stdout.flush().unwrap();
-    }};
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_print_color256!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_print_color256!(@colors None, Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
+    ($fg_num:expr, _, $($arg:tt)+) => {
+        $crate::try_print_color256!(@colors Some($crate::Color::Color256($fg_num)), None, $($arg)+)
+    };
+    ($fg_num:expr, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_print_color256!(@colors Some($crate::Color::Color256($fg_num)), Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
 }
 
 /// Same as `print_color256!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! println_color256 {
-    ($fg_num:expr, $bg_num:expr, $text:expr) => {{
-        use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stdout = std::io::stdout().lock();
-        write!(
-            &mut stdout,
-            "\u{001b}[{};{}m{}\u{001b}[0m\n",
-            Fg::get_ansi_code(&Color::Color256($fg_num)),
-            Bg::get_ansi_code(&Color::Color256($bg_num)),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_println_color256!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `println_color256!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_println_color256 {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stdout,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            true,
         )
-        .unwrap();
-        stdout.flush().unwrap();
-    }};
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_println_color256!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_println_color256!(@colors None, Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
+    ($fg_num:expr, _, $($arg:tt)+) => {
+        $crate::try_println_color256!(@colors Some($crate::Color::Color256($fg_num)), None, $($arg)+)
+    };
+    ($fg_num:expr, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_println_color256!(@colors Some($crate::Color::Color256($fg_num)), Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
 }
 
 /// Print color text to stderr.
 ///
 /// # Arguments
-/// * `$fg_num` - The foreground 256-color mode color number.
-/// * `$bg_num` - The background 256-color mode color number.
-/// * `$text` - The text to print to stderr.
+/// * `$fg_num` - The foreground 256-color mode color number, or `_` to leave
+///   the foreground color unchanged.
+/// * `$bg_num` - The background 256-color mode color number, or `_` to leave
+///   the background color unchanged.
+/// * `$fmt`, `$args` - The text to print to stderr, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -574,48 +872,83 @@ macro_rules! println_color256 {
 /// ```
 #[macro_export]
 macro_rules! eprint_color256 {
-    ($fg_num:expr, $bg_num:expr, $text:expr) => {{
-        use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stderr = std::io::stderr().lock();
-        write!(
-            &mut stderr,
-            "\u{001b}[{};{}m{}\u{001b}[0m",
-            Fg::get_ansi_code(&Color::Color256($fg_num)),
-            Bg::get_ansi_code(&Color::Color256($bg_num)),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprint_color256!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprint_color256!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprint_color256 {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stderr,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            false,
         )
-        .unwrap();
-        stderr.flush().unwrap();
-    }};
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_eprint_color256!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_eprint_color256!(@colors None, Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
+    ($fg_num:expr, _, $($arg:tt)+) => {
+        $crate::try_eprint_color256!(@colors Some($crate::Color::Color256($fg_num)), None, $($arg)+)
+    };
+    ($fg_num:expr, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_eprint_color256!(@colors Some($crate::Color::Color256($fg_num)), Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
 }
 
 /// Same as `eprint_color256!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! eprintln_color256 {
-    ($fg_num:expr, $bg_num:expr, $text:expr) => {{
-        use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stderr = std::io::stderr().lock();
-        write!(
-            &mut stderr,
-            "\u{001b}[{};{}m{}\u{001b}[0m\n",
-            Fg::get_ansi_code(&Color::Color256($fg_num)),
-            Bg::get_ansi_code(&Color::Color256($bg_num)),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprintln_color256!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprintln_color256!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprintln_color256 {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stderr,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            true,
         )
-        .unwrap();
-        stderr.flush().unwrap();
-    }};
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_eprintln_color256!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_eprintln_color256!(@colors None, Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
+    ($fg_num:expr, _, $($arg:tt)+) => {
+        $crate::try_eprintln_color256!(@colors Some($crate::Color::Color256($fg_num)), None, $($arg)+)
+    };
+    ($fg_num:expr, $bg_num:expr, $($arg:tt)+) => {
+        $crate::try_eprintln_color256!(@colors Some($crate::Color::Color256($fg_num)), Some($crate::Color::Color256($bg_num)), $($arg)+)
+    };
 }
 
 /// Write color text to a buffer using 24-bit RGB color values.
 ///
 /// # Arguments
 /// * `$buffer` - The destination buffer.
-/// * (`$fg_r`, `$fg_g`, `$fg_b`) - The foreground RGB value.
-/// * (`$bg_r`, `$bg_g`, `$bg_b`) - The background RGB value.
-/// * `$text` - The text to write.
+/// * (`$fg_r`, `$fg_g`, `$fg_b`) - The foreground RGB value, or `_` to leave
+///   the foreground color unchanged.
+/// * (`$bg_r`, `$bg_g`, `$bg_b`) - The background RGB value, or `_` to leave
+///   the background color unchanged.
+/// * `$fmt`, `$args` - The text to write, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -628,50 +961,107 @@ macro_rules! eprintln_color256 {
 /// write_rgb!(&mut buffer, (211, 0, 0), (255, 255, 255), "test");
 ///
 /// assert_eq!(buffer.as_slice(), b"\x1b[38;2;211;0;0;48;2;255;255;255mtest\x1b[0m");
+///
+/// // Write text in the current foreground color on a white background.
+/// buffer.clear();
+/// write_rgb!(&mut buffer, _, (255, 255, 255), "test");
+///
+/// assert_eq!(buffer.as_slice(), b"\x1b[48;2;255;255;255mtest\x1b[0m");
 /// ```
 #[macro_export]
 macro_rules! write_rgb {
-    ($buffer:expr, ($fg_r:expr, $fg_g:expr, $fg_b:expr),
-    ($bg_r:expr, $bg_g:expr, $bg_b:expr), $text:expr) => {{
+    ($($arg:tt)+) => {
+        $crate::try_write_rgb!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `write_rgb!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_write_rgb {
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
-        use $crate::{Bg, Color, Fg};
         write!(
             $buffer,
-            "\u{001b}[{};{}m{}\u{001b}[0m",
-            Fg::get_ansi_code(&Color::Rgb($fg_r, $fg_g, $fg_b)),
-            Bg::get_ansi_code(&Color::Rgb($bg_r, $bg_g, $bg_b)),
-            $text
+            "{}",
+            $crate::stream::write_args(
+                $crate::parse::color_codes($fg, $bg).as_str(),
+                format_args!($fmt $($arg)*)
+            )
         )
-        .unwrap();
-        $buffer.flush().unwrap();
     }};
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_write_rgb!(@colors $buffer, $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    ($buffer:expr, _,
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_write_rgb!(@colors $buffer, None, Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+    ($buffer:expr, ($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     _, $($arg:tt)+) => {
+        $crate::try_write_rgb!(@colors $buffer, Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), None, $($arg)+)
+    };
+    ($buffer:expr, ($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_write_rgb!(@colors $buffer, Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
 }
 
 /// Same as `write_rgb!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! writeln_rgb {
-    ($buffer:expr, ($fg_r:expr, $fg_g:expr, $fg_b:expr),
-    ($bg_r:expr, $bg_g:expr, $bg_b:expr), $text:expr) => {{
+    ($($arg:tt)+) => {
+        $crate::try_writeln_rgb!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `writeln_rgb!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_writeln_rgb {
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
-        use $crate::{Bg, Color, Fg};
         write!(
             $buffer,
-            "\u{001b}[{};{}m{}\u{001b}[0m\n",
-            Fg::get_ansi_code(&Color::Rgb($fg_r, $fg_g, $fg_b)),
-            Bg::get_ansi_code(&Color::Rgb($bg_r, $bg_g, $bg_b)),
-            $text
+            "{}\n",
+            $crate::stream::write_args(
+                $crate::parse::color_codes($fg, $bg).as_str(),
+                format_args!($fmt $($arg)*)
+            )
         )
-        .unwrap();
-        $buffer.flush().unwrap();
     }};
+    (@colors $buffer:expr, $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_writeln_rgb!(@colors $buffer, $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    ($buffer:expr, _,
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_writeln_rgb!(@colors $buffer, None, Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+    ($buffer:expr, ($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     _, $($arg:tt)+) => {
+        $crate::try_writeln_rgb!(@colors $buffer, Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), None, $($arg)+)
+    };
+    ($buffer:expr, ($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_writeln_rgb!(@colors $buffer, Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
 }
 
 /// Print color text to stdout using 24-bit RGB color values.
 ///
 /// # Arguments
-/// * (`$fg_r`, `$fg_g`, `$fg_b`) - The foreground RGB value.
-/// * (`$bg_r`, `$bg_g`, `$bg_b`) - The background RGB value.
-/// * `$text` - The text to print to stdout.
+/// * (`$fg_r`, `$fg_g`, `$fg_b`) - The foreground RGB value, or `_` to leave
+///   the foreground color unchanged.
+/// * (`$bg_r`, `$bg_g`, `$bg_b`) - The background RGB value, or `_` to leave
+///   the background color unchanged.
+/// * `$fmt`, `$args` - The text to print to stdout, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -683,49 +1073,88 @@ macro_rules! writeln_rgb {
 /// ```
 #[macro_export]
 macro_rules! print_rgb {
-    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
-     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $text:expr) => {{
-        use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stdout = std::io::stdout().lock();
-        write!(
-            &mut stdout,
-            "\u{001b}[{};{}m{}\u{001b}[0m",
-            Fg::get_ansi_code(&Color::Rgb($fg_r, $fg_g, $fg_b)),
-            Bg::get_ansi_code(&Color::Rgb($bg_r, $bg_g, $bg_b)),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_print_rgb!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `print_rgb!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_print_rgb {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stdout,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            false,
         )
-        .unwrap();
-        stdout.flush().unwrap();
-    }};
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_print_rgb!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_,
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_print_rgb!(@colors None, Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     _, $($arg:tt)+) => {
+        $crate::try_print_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), None, $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_print_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
 }
 
 /// Same as `print_rgb!()` but with a newline appended at the end.
 #[macro_export]
 macro_rules! println_rgb {
-    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
-     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $text:expr) => {{
-        use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stdout = std::io::stdout().lock();
-        write!(
-            &mut stdout,
-            "\u{001b}[{};{}m{}\u{001b}[0m\n",
-            Fg::get_ansi_code(&Color::Rgb($fg_r, $fg_g, $fg_b)),
-            Bg::get_ansi_code(&Color::Rgb($bg_r, $bg_g, $bg_b)),
-            $text
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_println_rgb!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `println_rgb!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_println_rgb {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stdout,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            true,
         )
-        .unwrap();
-        stdout.flush().unwrap();
-    }};
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_println_rgb!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_,
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_println_rgb!(@colors None, Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     _, $($arg:tt)+) => {
+        $crate::try_println_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), None, $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_println_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
 }
 
 /// Print color text to stderr using 24-bit RGB color values.
 ///
 /// # Arguments
-/// * (`$fg_r`, `$fg_g`, `$fg_b`) - The foreground RGB value.
-/// * (`$bg_r`, `$bg_g`, `$bg_b`) - The background RGB value.
-/// * `$text` - The text to print to stderr.
+/// * (`$fg_r`, `$fg_g`, `$fg_b`) - The foreground RGB value, or `_` to leave
+///   the foreground color unchanged.
+/// * (`$bg_r`, `$bg_g`, `$bg_b`) - The background RGB value, or `_` to leave
+///   the background color unchanged.
+/// * `$fmt`, `$args` - The text to print to stderr, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
 ///
 /// # Example
 ///
@@ -737,45 +1166,589 @@ macro_rules! println_rgb {
 /// ```
 #[macro_export]
 macro_rules! eprint_rgb {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprint_rgb!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprint_rgb!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprint_rgb {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stderr,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            false,
+        )
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_eprint_rgb!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_,
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_eprint_rgb!(@colors None, Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     _, $($arg:tt)+) => {
+        $crate::try_eprint_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), None, $($arg)+)
+    };
     (($fg_r:expr, $fg_g:expr, $fg_b:expr),
-     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $text:expr) => {{
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_eprint_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+}
+
+/// Same as `eprint_rgb!()` but with a newline appended at the end.
+#[macro_export]
+macro_rules! eprintln_rgb {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprintln_rgb!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprintln_rgb!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprintln_rgb {
+    (@colors $fg:expr, $bg:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print(
+            $crate::stream::Stream::Stderr,
+            $crate::parse::color_codes($fg, $bg).as_str(),
+            format_args!($fmt $($arg)*),
+            true,
+        )
+    };
+    (@colors $fg:expr, $bg:expr, $text:expr) => {
+        $crate::try_eprintln_rgb!(@colors $fg, $bg, "{}", $text)
+    };
+    // `_` leaves the foreground or background color unchanged.
+    (_,
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_eprintln_rgb!(@colors None, Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     _, $($arg:tt)+) => {
+        $crate::try_eprintln_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), None, $($arg)+)
+    };
+    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
+     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $($arg:tt)+) => {
+        $crate::try_eprintln_rgb!(@colors Some($crate::Color::Rgb($fg_r, $fg_g, $fg_b)), Some($crate::Color::Rgb($bg_r, $bg_g, $bg_b)), $($arg)+)
+    };
+}
+
+/// Write gradient text to a buffer, coloring each grapheme with the color
+/// between two RGB color stops (or the stops of a `Gradient`) at its position.
+///
+/// # Arguments
+/// * `$buffer` - The destination buffer.
+/// * (`$r1`, `$g1`, `$b1`), (`$r2`, `$g2`, `$b2`) - The RGB values of the first
+///   and last foreground colors, or a `Gradient` expression in place of both
+///   tuples for more stops, Oklab interpolation or a background gradient.
+/// * `$fmt`, `$args` - The text to write, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{write_gradient, Color, Gradient};
+///
+/// let mut buffer: Vec<u8> = vec![];
+///
+/// // Write text fading from red to blue to a buffer.
+/// write_gradient!(&mut buffer, (255, 0, 0), (0, 0, 255), "abc");
+///
+/// assert_eq!(
+///     buffer.as_slice(),
+///     b"\x1b[38;2;255;0;0ma\x1b[38;2;128;0;128mb\x1b[38;2;0;0;255mc\x1b[0m"
+/// );
+///
+/// // Write text on a background fading from black to white.
+/// let gradient = Gradient::new([Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255)]).background();
+/// buffer.clear();
+/// write_gradient!(&mut buffer, gradient, "ab");
+///
+/// assert_eq!(
+///     buffer.as_slice(),
+///     b"\x1b[48;2;0;0;0ma\x1b[48;2;255;255;255mb\x1b[0m"
+/// );
+/// ```
+#[macro_export]
+macro_rules! write_gradient {
+    ($($arg:tt)+) => {
+        $crate::try_write_gradient!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `write_gradient!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_write_gradient {
+    (@gradient $buffer:expr, $gradient:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stderr = std::io::stderr().lock();
         write!(
-            &mut stderr,
-            "\u{001b}[{};{}m{}\u{001b}[0m",
-            Fg::get_ansi_code(&Color::Rgb($fg_r, $fg_g, $fg_b)),
-            Bg::get_ansi_code(&Color::Rgb($bg_r, $bg_g, $bg_b)),
-            $text
+            $buffer,
+            "{}",
+            $crate::stream::write_gradient($gradient, format_args!($fmt $($arg)*))
         )
-        .unwrap();
-        stderr.flush().unwrap();
     }};
+    (@gradient $buffer:expr, $gradient:expr, $text:expr) => {
+        $crate::try_write_gradient!(@gradient $buffer, $gradient, "{}", $text)
+    };
+    ($buffer:expr, ($r1:expr, $g1:expr, $b1:expr),
+     ($r2:expr, $g2:expr, $b2:expr), $($arg:tt)+) => {
+        $crate::try_write_gradient!(@gradient $buffer, &$crate::Gradient::new([$crate::Color::Rgb($r1, $g1, $b1), $crate::Color::Rgb($r2, $g2, $b2)]), $($arg)+)
+    };
+    ($buffer:expr, $gradient:expr, $($arg:tt)+) => {
+        $crate::try_write_gradient!(@gradient $buffer, &$gradient, $($arg)+)
+    };
 }
 
-/// Same as `eprint_rgb!()` but with a newline appended at the end.
+/// Same as `write_gradient!()` but with a newline appended at the end.
 #[macro_export]
-macro_rules! eprintln_rgb {
-    (($fg_r:expr, $fg_g:expr, $fg_b:expr),
-     ($bg_r:expr, $bg_g:expr, $bg_b:expr), $text:expr) => {{
+macro_rules! writeln_gradient {
+    ($($arg:tt)+) => {
+        $crate::try_writeln_gradient!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `writeln_gradient!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_writeln_gradient {
+    (@gradient $buffer:expr, $gradient:expr, $fmt:literal $($arg:tt)*) => {{
+        #[allow(unused_imports)]
         use std::io::Write;
-        use $crate::{Bg, Color, Fg};
-        let mut stderr = std::io::stderr().lock();
         write!(
-            &mut stderr,
-            "\u{001b}[{};{}m{}\u{001b}[0m\n",
-            Fg::get_ansi_code(&Color::Rgb($fg_r, $fg_g, $fg_b)),
-            Bg::get_ansi_code(&Color::Rgb($bg_r, $bg_g, $bg_b)),
-            $text
+            $buffer,
+            "{}\n",
+            $crate::stream::write_gradient($gradient, format_args!($fmt $($arg)*))
+        )
+    }};
+    (@gradient $buffer:expr, $gradient:expr, $text:expr) => {
+        $crate::try_writeln_gradient!(@gradient $buffer, $gradient, "{}", $text)
+    };
+    ($buffer:expr, ($r1:expr, $g1:expr, $b1:expr),
+     ($r2:expr, $g2:expr, $b2:expr), $($arg:tt)+) => {
+        $crate::try_writeln_gradient!(@gradient $buffer, &$crate::Gradient::new([$crate::Color::Rgb($r1, $g1, $b1), $crate::Color::Rgb($r2, $g2, $b2)]), $($arg)+)
+    };
+    ($buffer:expr, $gradient:expr, $($arg:tt)+) => {
+        $crate::try_writeln_gradient!(@gradient $buffer, &$gradient, $($arg)+)
+    };
+}
+
+/// Print gradient text to stdout, coloring each grapheme with the color
+/// between two RGB color stops (or the stops of a `Gradient`) at its position.
+///
+/// The whole text is written to stdout at once.
+///
+/// # Arguments
+/// * (`$r1`, `$g1`, `$b1`), (`$r2`, `$g2`, `$b2`) - The RGB values of the first
+///   and last foreground colors, or a `Gradient` expression in place of both
+///   tuples for more stops, Oklab interpolation or a background gradient.
+/// * `$fmt`, `$args` - The text to print to stdout, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{print_gradient, Color, Gradient, Interpolation};
+///
+/// // Print text fading from red to blue to stdout.
+/// print_gradient!((255, 0, 0), (0, 0, 255), "test");
+///
+/// // Print text fading from red through green to blue in Oklab.
+/// let gradient = Gradient::new([Color::Red, Color::Green, Color::Blue])
+///     .interpolation(Interpolation::Oklab);
+/// print_gradient!(gradient, "test");
+/// ```
+#[macro_export]
+macro_rules! print_gradient {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_print_gradient!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `print_gradient!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_print_gradient {
+    (@gradient $gradient:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print_gradient(
+            $crate::stream::Stream::Stdout,
+            $gradient,
+            format_args!($fmt $($arg)*),
+            false,
+        )
+    };
+    (@gradient $gradient:expr, $text:expr) => {
+        $crate::try_print_gradient!(@gradient $gradient, "{}", $text)
+    };
+    (($r1:expr, $g1:expr, $b1:expr),
+     ($r2:expr, $g2:expr, $b2:expr), $($arg:tt)+) => {
+        $crate::try_print_gradient!(@gradient &$crate::Gradient::new([$crate::Color::Rgb($r1, $g1, $b1), $crate::Color::Rgb($r2, $g2, $b2)]), $($arg)+)
+    };
+    ($gradient:expr, $($arg:tt)+) => {
+        $crate::try_print_gradient!(@gradient &$gradient, $($arg)+)
+    };
+}
+
+/// Same as `print_gradient!()` but with a newline appended at the end.
+#[macro_export]
+macro_rules! println_gradient {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_println_gradient!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `println_gradient!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_println_gradient {
+    (@gradient $gradient:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print_gradient(
+            $crate::stream::Stream::Stdout,
+            $gradient,
+            format_args!($fmt $($arg)*),
+            true,
+        )
+    };
+    (@gradient $gradient:expr, $text:expr) => {
+        $crate::try_println_gradient!(@gradient $gradient, "{}", $text)
+    };
+    (($r1:expr, $g1:expr, $b1:expr),
+     ($r2:expr, $g2:expr, $b2:expr), $($arg:tt)+) => {
+        $crate::try_println_gradient!(@gradient &$crate::Gradient::new([$crate::Color::Rgb($r1, $g1, $b1), $crate::Color::Rgb($r2, $g2, $b2)]), $($arg)+)
+    };
+    ($gradient:expr, $($arg:tt)+) => {
+        $crate::try_println_gradient!(@gradient &$gradient, $($arg)+)
+    };
+}
+
+/// Print gradient text to stderr, coloring each grapheme with the color
+/// between two RGB color stops (or the stops of a `Gradient`) at its position.
+///
+/// The whole text is written to stderr at once.
+///
+/// # Arguments
+/// * (`$r1`, `$g1`, `$b1`), (`$r2`, `$g2`, `$b2`) - The RGB values of the first
+///   and last foreground colors, or a `Gradient` expression in place of both
+///   tuples for more stops, Oklab interpolation or a background gradient.
+/// * `$fmt`, `$args` - The text to print to stderr, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{eprint_gradient, Color, Gradient, Interpolation};
+///
+/// // Print text fading from red to blue to stderr.
+/// eprint_gradient!((255, 0, 0), (0, 0, 255), "test");
+///
+/// // Print text fading from red through green to blue in Oklab.
+/// let gradient = Gradient::new([Color::Red, Color::Green, Color::Blue])
+///     .interpolation(Interpolation::Oklab);
+/// eprint_gradient!(gradient, "test");
+/// ```
+#[macro_export]
+macro_rules! eprint_gradient {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprint_gradient!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprint_gradient!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprint_gradient {
+    (@gradient $gradient:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print_gradient(
+            $crate::stream::Stream::Stderr,
+            $gradient,
+            format_args!($fmt $($arg)*),
+            false,
+        )
+    };
+    (@gradient $gradient:expr, $text:expr) => {
+        $crate::try_eprint_gradient!(@gradient $gradient, "{}", $text)
+    };
+    (($r1:expr, $g1:expr, $b1:expr),
+     ($r2:expr, $g2:expr, $b2:expr), $($arg:tt)+) => {
+        $crate::try_eprint_gradient!(@gradient &$crate::Gradient::new([$crate::Color::Rgb($r1, $g1, $b1), $crate::Color::Rgb($r2, $g2, $b2)]), $($arg)+)
+    };
+    ($gradient:expr, $($arg:tt)+) => {
+        $crate::try_eprint_gradient!(@gradient &$gradient, $($arg)+)
+    };
+}
+
+/// Same as `eprint_gradient!()` but with a newline appended at the end.
+#[macro_export]
+macro_rules! eprintln_gradient {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprintln_gradient!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprintln_gradient!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprintln_gradient {
+    (@gradient $gradient:expr, $fmt:literal $($arg:tt)*) => {
+        $crate::stream::print_gradient(
+            $crate::stream::Stream::Stderr,
+            $gradient,
+            format_args!($fmt $($arg)*),
+            true,
+        )
+    };
+    (@gradient $gradient:expr, $text:expr) => {
+        $crate::try_eprintln_gradient!(@gradient $gradient, "{}", $text)
+    };
+    (($r1:expr, $g1:expr, $b1:expr),
+     ($r2:expr, $g2:expr, $b2:expr), $($arg:tt)+) => {
+        $crate::try_eprintln_gradient!(@gradient &$crate::Gradient::new([$crate::Color::Rgb($r1, $g1, $b1), $crate::Color::Rgb($r2, $g2, $b2)]), $($arg)+)
+    };
+    ($gradient:expr, $($arg:tt)+) => {
+        $crate::try_eprintln_gradient!(@gradient &$gradient, $($arg)+)
+    };
+}
+
+/// Write text with several styles to a buffer using markup.
+///
+/// A tag such as `<bold red>` turns on a style string (see `parse_markup`)
+/// until the matching `</>`, tags can be nested, and a literal `<` is
+/// written as `<<`. The markup in a literal format string is validated at
+/// compile time.
+///
+/// # Arguments
+/// * `$buffer` - The destination buffer.
+/// * `$fmt`, `$args` - The markup to write, given as a format string and
+///   arguments like `format!()` (or as a single `Display` expression).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::write_markup;
+///
+/// let mut buffer: Vec<u8> = vec![];
+///
+/// write_markup!(&mut buffer, "<bold red>error</>: file <cyan>{}</> not found", "a.txt");
+///
+/// assert_eq!(
+///     buffer.as_slice(),
+///     b"\x1b[1;31merror\x1b[0m: file \x1b[36ma.txt\x1b[0m not found"
+/// );
+/// ```
+///
+/// Unbalanced tags in a literal format string are a compile error:
+///
+/// ```rust,compile_fail
+/// use color_macros::write_markup;
+///
+/// let mut buffer: Vec<u8> = vec![];
+///
+/// // error: invalid markup: unclosed tag at position 0
+/// write_markup!(&mut buffer, "<bold red>error: {}", 1);
+/// ```
+#[macro_export]
+macro_rules! write_markup {
+    ($($arg:tt)+) => {
+        $crate::try_write_markup!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `write_markup!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_write_markup {
+    // Literal markup is validated at compile time.
+    ($buffer:expr, $fmt:literal $($arg:tt)*) => {{
+        const _: () = $crate::parse::check_markup($fmt);
+        #[allow(unused_imports)]
+        use std::io::Write;
+        write!($buffer, "{}", $crate::stream::write_markup(format_args!($fmt $($arg)*)))
+    }};
+    ($buffer:expr, $text:expr) => {
+        $crate::try_write_markup!($buffer, "{}", $text)
+    };
+}
+
+/// Same as `write_markup!()` but with a newline appended at the end.
+#[macro_export]
+macro_rules! writeln_markup {
+    ($($arg:tt)+) => {
+        $crate::try_writeln_markup!($($arg)+).unwrap()
+    };
+}
+
+/// Same as `writeln_markup!()`, but returns an `std::io::Result<()>` (or a
+/// `std::fmt::Result` when writing to a `std::fmt::Write` buffer) instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_writeln_markup {
+    // Literal markup is validated at compile time.
+    ($buffer:expr, $fmt:literal $($arg:tt)*) => {{
+        const _: () = $crate::parse::check_markup($fmt);
+        #[allow(unused_imports)]
+        use std::io::Write;
+        write!($buffer, "{}\n", $crate::stream::write_markup(format_args!($fmt $($arg)*)))
+    }};
+    ($buffer:expr, $text:expr) => {
+        $crate::try_writeln_markup!($buffer, "{}", $text)
+    };
+}
+
+/// Print text with several styles to stdout using markup.
+///
+/// A tag such as `<bold red>` turns on a style string (see `parse_markup`)
+/// until the matching `</>`, tags can be nested, and a literal `<` is
+/// written as `<<`. The markup in a literal format string is validated at
+/// compile time, and the whole text is written to stdout at once.
+///
+/// # Arguments
+/// * `$fmt`, `$args` - The markup to print to stdout, given as a format string
+///   and arguments like `format!()` (or as a single `Display` expression).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::print_markup;
+///
+/// let path = "a.txt";
+///
+/// print_markup!("<bold red>error</>: file <cyan>{path}</> not found");
+/// ```
+#[macro_export]
+macro_rules! print_markup {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_print_markup!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `print_markup!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_print_markup {
+    // Literal markup is validated at compile time.
+    ($fmt:literal $($arg:tt)*) => {{
+        const _: () = $crate::parse::check_markup($fmt);
+        $crate::stream::print_markup(
+            $crate::stream::Stream::Stdout,
+            format_args!($fmt $($arg)*),
+            false,
         )
-        .unwrap();
-        stderr.flush().unwrap();
     }};
+    ($text:expr) => {
+        $crate::try_print_markup!("{}", $text)
+    };
+}
+
+/// Same as `print_markup!()` but with a newline appended at the end.
+#[macro_export]
+macro_rules! println_markup {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_println_markup!($($arg)+), "stdout")
+    };
+}
+
+/// Same as `println_markup!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_println_markup {
+    // Literal markup is validated at compile time.
+    ($fmt:literal $($arg:tt)*) => {{
+        const _: () = $crate::parse::check_markup($fmt);
+        $crate::stream::print_markup(
+            $crate::stream::Stream::Stdout,
+            format_args!($fmt $($arg)*),
+            true,
+        )
+    }};
+    ($text:expr) => {
+        $crate::try_println_markup!("{}", $text)
+    };
+}
+
+/// Print text with several styles to stderr using markup.
+///
+/// A tag such as `<bold red>` turns on a style string (see `parse_markup`)
+/// until the matching `</>`, tags can be nested, and a literal `<` is
+/// written as `<<`. The markup in a literal format string is validated at
+/// compile time, and the whole text is written to stderr at once.
+///
+/// # Arguments
+/// * `$fmt`, `$args` - The markup to print to stderr, given as a format string
+///   and arguments like `format!()` (or as a single `Display` expression).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::eprint_markup;
+///
+/// let path = "a.txt";
+///
+/// eprint_markup!("<bold red>error</>: file <cyan>{path}</> not found");
+/// ```
+#[macro_export]
+macro_rules! eprint_markup {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprint_markup!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprint_markup!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprint_markup {
+    // Literal markup is validated at compile time.
+    ($fmt:literal $($arg:tt)*) => {{
+        const _: () = $crate::parse::check_markup($fmt);
+        $crate::stream::print_markup(
+            $crate::stream::Stream::Stderr,
+            format_args!($fmt $($arg)*),
+            false,
+        )
+    }};
+    ($text:expr) => {
+        $crate::try_eprint_markup!("{}", $text)
+    };
+}
+
+/// Same as `eprint_markup!()` but with a newline appended at the end.
+#[macro_export]
+macro_rules! eprintln_markup {
+    ($($arg:tt)+) => {
+        $crate::stream::handle_print_result($crate::try_eprintln_markup!($($arg)+), "stderr")
+    };
+}
+
+/// Same as `eprintln_markup!()`, but returns an `std::io::Result<()>` instead of
+/// panicking when writing fails.
+#[macro_export]
+macro_rules! try_eprintln_markup {
+    // Literal markup is validated at compile time.
+    ($fmt:literal $($arg:tt)*) => {{
+        const _: () = $crate::parse::check_markup($fmt);
+        $crate::stream::print_markup(
+            $crate::stream::Stream::Stderr,
+            format_args!($fmt $($arg)*),
+            true,
+        )
+    }};
+    ($text:expr) => {
+        $crate::try_eprintln_markup!("{}", $text)
+    };
 }
 
 #[cfg(test)]
 mod tests {
+    #[allow(unused_imports)]
     use super::{write_color256, write_rgb, write_styled};
 
     // Test all color256 foreground and background color combinations
@@ -831,6 +1804,209 @@ mod tests {
         assert!(&output[..] == &expected[..]);
     }
 
+    // Test foreground-only and background-only colors
+    #[test]
+    fn test_color256_and_rgb_single_colors() {
+        let mut output = Vec::new();
+        let num = 42;
+
+        write_color256!(&mut output, 196, _, "a");
+        write_color256!(&mut output, _, num + 1, "b");
+        writeln_color256!(&mut output, _, 0, "{}", num);
+        write_rgb!(&mut output, (1, 2, 3), _, "c");
+        write_rgb!(&mut output, _, (4, 5, 6), "d");
+        writeln_rgb!(&mut output, (7, 8, 9), _, "{num}");
+
+        let expected = concat!(
+            "\x1b[38;5;196ma\x1b[0m",
+            "\x1b[48;5;43mb\x1b[0m",
+            "\x1b[48;5;0m42\x1b[0m\n",
+            "\x1b[38;2;1;2;3mc\x1b[0m",
+            "\x1b[48;2;4;5;6md\x1b[0m",
+            "\x1b[38;2;7;8;9m42\x1b[0m\n",
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    // Test that the styled macros accept a Style or a runtime style string
+    #[test]
+    fn test_write_styled_style_values() {
+        use crate::{Color, Style};
+
+        let mut output = Vec::new();
+        let style = Style::new().fg(Color::Color256(208)).italic();
+        let name = String::from("bright blue on red");
+
+        write_styled!(&mut output, style, "a");
+        write_styled!(&mut output, &style, "{}", 1);
+        writeln_styled!(&mut output, Style::new().on(Color::Green), "b");
+        write_styled!(&mut output, name, "c");
+        write_styled!(&mut output, name.as_str(), "d");
+
+        let expected = concat!(
+            "\x1b[3;38;5;208ma\x1b[0m",
+            "\x1b[3;38;5;208m1\x1b[0m",
+            "\x1b[42mb\x1b[0m\n",
+            "\x1b[94;41mc\x1b[0m",
+            "\x1b[94;41md\x1b[0m",
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    // Test that the write macros honor the color choice
+    #[test]
+    fn test_write_macros_color_choice() {
+        use crate::{scoped_color_choice, ColorChoice};
+
+        let mut output = Vec::new();
+        {
+            let _guard = scoped_color_choice(ColorChoice::Never);
+            write_styled!(&mut output, "bold red", "a");
+            writeln_color256!(&mut output, 196, _, "{}", 1);
+            write_rgb!(&mut output, _, (1, 2, 3), "c");
+        }
+        write_styled!(&mut output, "red", "d");
+
+        assert_eq!(String::from_utf8(output).unwrap(), "a1\nc\x1b[31md\x1b[0m");
+    }
+
+    // Test that format strings and arguments are forwarded to every write macro
+    #[test]
+    fn test_write_macros_format_args() {
+        let mut output = Vec::new();
+        let (x, y) = (7, "ab");
+
+        write_styled!(&mut output, "red", "x = {} ({:>4})", x, y);
+        write_color256!(&mut output, 196, 255, "[{:03}]", x);
+        write_rgb!(&mut output, (1, 2, 3), (4, 5, 6), "{y}{x}",);
+        write_styled!(&mut output, "on blue", "{{}}");
+        write_styled!(&mut output, "green", y.to_uppercase());
+
+        let expected = concat!(
+            "\x1b[31mx = 7 (  ab)\x1b[0m",
+            "\x1b[38;5;196;48;5;255m[007]\x1b[0m",
+            "\x1b[38;2;1;2;3;48;2;4;5;6mab7\x1b[0m",
+            "\x1b[44m{}\x1b[0m",
+            "\x1b[32mAB\x1b[0m",
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_gradient_macros() {
+        use crate::{Color, Gradient};
+
+        let mut output = Vec::new();
+        let gradient = Gradient::new([Color::Rgb(0, 0, 0), Color::Rgb(0, 0, 100)]).background();
+
+        write_gradient!(&mut output, (10, 0, 0), (30, 0, 0), "{}{}", 1, 2);
+        writeln_gradient!(&mut output, gradient, "x{:>2}", 'y');
+        write_gradient!(&mut output, &gradient, String::from("ab"));
+
+        let expected = concat!(
+            "\x1b[38;2;10;0;0m1\x1b[38;2;30;0;0m2\x1b[0m",
+            "\x1b[48;2;0;0;0mx\x1b[48;2;0;0;50m \x1b[48;2;0;0;100my\x1b[0m\n",
+            "\x1b[48;2;0;0;0ma\x1b[48;2;0;0;100mb\x1b[0m",
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    // Test that the gradient macros also write to `fmt::Write` buffers
+    #[test]
+    fn test_gradient_macros_write_to_string() {
+        use std::fmt::Write as _;
+
+        let mut output = String::new();
+        assert!(try_write_gradient!(&mut output, (0, 0, 0), (0, 0, 100), "ab").is_ok());
+        assert!(try_writeln_gradient!(&mut output, (10, 0, 0), (30, 0, 0), "{}", 1).is_ok());
+
+        let expected = concat!(
+            "\x1b[38;2;0;0;0ma\x1b[38;2;0;0;100mb\x1b[0m",
+            "\x1b[38;2;10;0;0m1\x1b[0m\n",
+        );
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_styled_theme_names() {
+        let mut output = Vec::new();
+        let style = String::from("Success");
+
+        write_styled!(&mut output, "error", "a");
+        write_styled!(&mut output, style, "b");
+        write_styled!(&mut output, "not-a-theme-name", "c");
+        write_styled!(&mut output, "ok", "");
+        write_styled!(&mut output, String::from("not-a-theme-name"), "d");
+
+        let expected = concat!("\x1b[1;31ma\x1b[0m", "\x1b[1;32mb\x1b[0m", "cd");
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_markup_macros() {
+        let mut output = Vec::new();
+        let (name, width) = ("a<b", 5);
+
+        write_markup!(&mut output, "<bold red>error</>: <<{:<width$}> <{}>x</>", name, "on blue");
+        writeln_markup!(&mut output, "<cyan>{}</>", crate::escape_markup(name));
+        write_markup!(&mut output, String::from("<red>x"));
+        write_markup!(&mut output, "<{:>4}>y</>", "red");
+
+        let expected = concat!(
+            "\x1b[1;31merror\x1b[0m: <a<b  > \x1b[44mx\x1b[0m",
+            "\x1b[36ma<b\x1b[0m\n",
+            "\x1b[31mx\x1b[0m",
+            "\x1b[31my\x1b[0m",
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    // Test that the try_ macros return write errors instead of panicking
+    #[test]
+    fn test_try_write_macros_return_errors() {
+        struct FailingWriter;
+
+        impl std::io::Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut output = Vec::new();
+        assert!(try_write_styled!(&mut output, "red", "hi").is_ok());
+        assert!(try_writeln_color256!(&mut output, 1, 2, "{}", 3).is_ok());
+        assert_eq!(output, b"\x1b[31mhi\x1b[0m\x1b[38;5;1;48;5;2m3\x1b[0m\n");
+
+        let mut failing = FailingWriter;
+        assert!(try_write_styled!(&mut failing, "red", "hi").is_err());
+        assert!(try_writeln_color256!(&mut failing, 1, 2, "hi").is_err());
+        assert!(try_write_rgb!(&mut failing, (1, 2, 3), (4, 5, 6), "hi").is_err());
+    }
+
+    // Test that the color256 and RGB macros also write to `fmt::Write` buffers
+    #[test]
+    fn test_color_macros_write_to_string() {
+        use std::fmt::Write as _;
+
+        let mut output = String::new();
+        assert!(try_write_color256!(&mut output, 1, _, "a").is_ok());
+        assert!(try_writeln_color256!(&mut output, _, 2, "{}", 'b').is_ok());
+        assert!(try_write_rgb!(&mut output, (1, 2, 3), _, "c").is_ok());
+        assert!(try_writeln_rgb!(&mut output, _, (4, 5, 6), "{}", 'd').is_ok());
+
+        let expected = concat!(
+            "\x1b[38;5;1ma\x1b[0m",
+            "\x1b[48;5;2mb\x1b[0m\n",
+            "\x1b[38;2;1;2;3mc\x1b[0m",
+            "\x1b[48;2;4;5;6md\x1b[0m\n",
+        );
+        assert_eq!(output, expected);
+    }
+
     // Test all write_styled foreground and background color combinations
     #[test]
     fn test_all_write_styled_fg_and_bg_color_combos() {
@@ -0,0 +1,339 @@
+//! A small markup language for text with several styles, e.g.
+//! `"<bold red>error</>: file <cyan>{}</> not found"`.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::{parse, theme, ColorDepth, ParseStyleError, Style};
+
+/// Parses markup into spans of text and the style of each span.
+///
+/// A tag such as `<bold red>` turns on a style (any style string, including
+/// color values such as `<#ff8800>` and theme names such as `<error>`) until
+/// the matching `</>`. Tags can be nested: an inner style replaces the
+/// colors it sets and adds its attributes to those of the outer style. A
+/// literal `<` is written as `<<`.
+///
+/// # Errors
+///
+/// Returns a `MarkupError` for an unterminated tag, a closing tag without an
+/// opening tag, an opening tag that is never closed, or a tag with an
+/// invalid style string.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{parse_markup, Color, Style};
+///
+/// let spans = parse_markup("<red>a <bold>b</></> <<c").unwrap();
+/// let red = Style::new().fg(Color::Red);
+///
+/// assert_eq!(
+///     spans,
+///     [(red, "a "), (red.bold(), "b"), (Style::new(), " "), (Style::new(), "<"), (Style::new(), "c")]
+/// );
+///
+/// let err = parse_markup("<bold>never closed").unwrap_err();
+/// assert_eq!(err.to_string(), "tag \"<bold>\" at position 0 is never closed");
+/// ```
+pub fn parse_markup(markup: &str) -> Result<Vec<(Style, &str)>, MarkupError> {
+    parse(markup, true)
+}
+
+/// Returns `text` with every `<` doubled, so that markup displays it as is
+/// (e.g. untrusted text passed as an argument of `print_markup!()`).
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{escape_markup, write_markup};
+///
+/// let mut buffer: Vec<u8> = vec![];
+/// let name = "Vec<u8>";
+///
+/// write_markup!(&mut buffer, "<bold>{}</>", escape_markup(name));
+///
+/// assert_eq!(buffer.as_slice(), b"\x1b[1mVec<u8>\x1b[0m");
+/// ```
+#[must_use]
+pub fn escape_markup(text: &str) -> Cow<'_, str> {
+    if text.contains('<') {
+        Cow::Owned(text.replace('<', "<<"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Appends `markup` with the escape codes of its tags to `out`, downgrading
+/// the colors to `depth`. `None` appends the text without any escape codes.
+///
+/// Invalid markup is rendered leniently rather than rejected, since it may
+/// come from the arguments of a macro: a tag that is not a valid style and a
+/// closing tag without an opening tag are written as text, and tags that are
+/// never closed end with the text.
+pub fn render(out: &mut String, markup: &str, depth: Option<ColorDepth>) {
+    let spans = parse(markup, false).unwrap_or_default();
+
+    let Some(depth) = depth else {
+        for (_, text) in spans {
+            out.push_str(text);
+        }
+        return;
+    };
+
+    let mut active = Style::new();
+
+    for (style, text) in spans {
+        if style != active {
+            if !active.is_plain() {
+                out.push_str("\u{001b}[0m");
+            }
+            if !style.is_plain() {
+                let codes = parse::codes(&style);
+                out.push_str("\u{001b}[");
+                out.push_str(&parse::downgrade_codes(codes.as_str(), depth));
+            }
+            active = style;
+        }
+        out.push_str(text);
+    }
+
+    if !active.is_plain() {
+        out.push_str("\u{001b}[0m");
+    }
+}
+
+/// Returns the style of the tag `<tag>`, which is looked up in the theme
+/// before it is parsed as a style string.
+fn tag_style(tag: &str) -> Result<Style, ParseStyleError> {
+    theme::lookup(tag).map_or_else(|| tag.parse(), Ok)
+}
+
+/// Returns `inner` nested inside of `outer`.
+fn nest(outer: Style, inner: Style) -> Style {
+    Style {
+        fg: inner.fg.or(outer.fg),
+        bg: inner.bg.or(outer.bg),
+        attrs: outer.attrs | inner.attrs,
+    }
+}
+
+/// Parses `markup` into styled spans. Errors are only returned if `strict`
+/// is true; otherwise the offending text is kept as text.
+fn parse(markup: &str, strict: bool) -> Result<Vec<(Style, &str)>, MarkupError> {
+    let mut spans = Vec::new();
+    // The styles of the open tags and the position and text of each tag.
+    let mut open: Vec<(Style, usize, &str)> = Vec::new();
+    let mut pos = 0;
+
+    let error = |kind, position, tag: &str| MarkupError { kind, position, tag: tag.to_string() };
+
+    while pos < markup.len() {
+        let style = open.last().map_or_else(Style::new, |&(style, _, _)| style);
+
+        let Some(start) = markup[pos..].find('<').map(|idx| pos + idx) else {
+            spans.push((style, &markup[pos..]));
+            break;
+        };
+
+        if start > pos {
+            spans.push((style, &markup[pos..start]));
+        }
+
+        let rest = &markup[start + 1..];
+
+        if rest.starts_with('<') {
+            spans.push((style, &markup[start..=start]));
+            pos = start + 2;
+            continue;
+        }
+
+        let Some(len) = rest.find('>') else {
+            if strict {
+                return Err(error(MarkupErrorKind::UnterminatedTag, start, ""));
+            }
+            spans.push((style, &markup[start..]));
+            break;
+        };
+
+        let tag = &rest[..len];
+        let end = start + len + 2;
+
+        if tag == "/" {
+            if open.pop().is_none() {
+                if strict {
+                    return Err(error(MarkupErrorKind::UnexpectedClosingTag, start, tag));
+                }
+                spans.push((style, &markup[start..end]));
+            }
+        } else {
+            match tag_style(tag) {
+                Ok(inner) => open.push((nest(style, inner), start, tag)),
+                Err(e) if strict => {
+                    return Err(error(MarkupErrorKind::InvalidStyle(e), start, tag));
+                }
+                Err(_) => spans.push((style, &markup[start..end])),
+            }
+        }
+        pos = end;
+    }
+
+    match open.last() {
+        Some(&(_, start, tag)) if strict => {
+            Err(error(MarkupErrorKind::UnclosedTag, start, tag))
+        }
+        _ => Ok(spans),
+    }
+}
+
+/// An error returned when parsing invalid markup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkupError {
+    kind: MarkupErrorKind,
+    position: usize,
+    tag: String,
+}
+
+impl MarkupError {
+    /// Returns the kind of error.
+    #[must_use]
+    pub const fn kind(&self) -> &MarkupErrorKind {
+        &self.kind
+    }
+
+    /// Returns the byte offset of the offending tag in the markup.
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the text between the angle brackets of the offending tag,
+    /// which is empty for an unterminated tag.
+    #[must_use]
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (tag, position) = (&self.tag, self.position);
+
+        match &self.kind {
+            MarkupErrorKind::UnterminatedTag => write!(
+                f,
+                "unterminated tag at position {position}; write \"<<\" for a literal \"<\""
+            ),
+            MarkupErrorKind::UnexpectedClosingTag => write!(
+                f,
+                "closing tag \"</>\" at position {position} has no matching opening tag"
+            ),
+            MarkupErrorKind::UnclosedTag => {
+                write!(f, "tag \"<{tag}>\" at position {position} is never closed")
+            }
+            MarkupErrorKind::InvalidStyle(e) => {
+                write!(f, "invalid style in tag \"<{tag}>\" at position {position}: {e}")
+            }
+        }
+    }
+}
+
+impl Error for MarkupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            MarkupErrorKind::InvalidStyle(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The kinds of errors that can occur while parsing markup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MarkupErrorKind {
+    /// A `<` is not followed by a `>` (a literal `<` is written as `<<`).
+    UnterminatedTag,
+    /// A closing tag `</>` has no matching opening tag.
+    UnexpectedClosingTag,
+    /// An opening tag is never closed.
+    UnclosedTag,
+    /// The text of a tag is not a valid style string or theme name.
+    InvalidStyle(ParseStyleError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_markup, render, MarkupErrorKind};
+    use crate::{Color, ColorDepth, Style};
+
+    /// Returns `markup` rendered at the color depth `depth`.
+    fn rendered(markup: &str, depth: Option<ColorDepth>) -> String {
+        let mut out = String::new();
+        render(&mut out, markup, depth);
+        out
+    }
+
+    #[test]
+    fn test_parse_nested_tags() {
+        let spans = parse_markup("<red on blue>a<bold #ff8800>b</>c</>d").unwrap();
+        let outer = Style::new().fg(Color::Red).on(Color::Blue);
+
+        assert_eq!(
+            spans,
+            [
+                (outer, "a"),
+                (outer.fg(Color::Rgb(255, 136, 0)).bold(), "b"),
+                (outer, "c"),
+                (Style::new(), "d"),
+            ]
+        );
+        assert_eq!(parse_markup("<warning>!</>").unwrap()[0].0, Style::new().fg(Color::Yellow).bold());
+        assert_eq!(parse_markup("").unwrap(), []);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let kind = |markup| parse_markup(markup).unwrap_err().kind().clone();
+
+        assert_eq!(kind("a < b"), MarkupErrorKind::UnterminatedTag);
+        assert_eq!(kind("a</>"), MarkupErrorKind::UnexpectedClosingTag);
+        assert_eq!(kind("<red><bold></>"), MarkupErrorKind::UnclosedTag);
+        assert!(matches!(kind("<bold purpel>x</>"), MarkupErrorKind::InvalidStyle(_)));
+
+        let err = parse_markup("ok <bold purpel>x</>").unwrap_err();
+        assert_eq!((err.position(), err.tag()), (3, "bold purpel"));
+        assert_eq!(
+            err.to_string(),
+            "invalid style in tag \"<bold purpel>\" at position 3: unknown color or attribute \
+             name \"purpel\" at position 5; did you mean \"magenta\"?"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let depth = Some(ColorDepth::TrueColor);
+
+        assert_eq!(
+            rendered("<bold red>error</>: <<file> <cyan>x</>", depth),
+            "\x1b[1;31merror\x1b[0m: <file> \x1b[36mx\x1b[0m"
+        );
+        assert_eq!(
+            rendered("<red>a<bold>b</>", depth),
+            "\x1b[31ma\x1b[0m\x1b[1;31mb\x1b[0m"
+        );
+        assert_eq!(rendered("<bold red>error</>: <<x", None), "error: <x");
+        assert_eq!(
+            rendered("<#ff0000>a</>", Some(ColorDepth::Ansi16)),
+            "\x1b[91ma\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_render_invalid_markup_as_text() {
+        let depth = Some(ColorDepth::TrueColor);
+
+        assert_eq!(rendered("Vec<u8> </> a < b", depth), "Vec<u8> </> a < b");
+        assert_eq!(rendered("<red>Option<T>", depth), "\x1b[31mOption<T>\x1b[0m");
+    }
+}
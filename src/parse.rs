@@ -0,0 +1,1264 @@
+//! Parsing of style strings.
+//!
+//! The parser is made of `const fn`s so that `parse_colors!()` can
+//! parse and validate a literal style string while the calling crate is being
+//! compiled. An invalid literal style string becomes a compile error that
+//! names the offending token, and a valid one becomes a `&'static str`
+//! containing the final ANSI color code(s), unless it names a style of the
+//! theme at runtime. The same parser backs `Style`'s and `Color`'s `FromStr`
+//! implementations and runtime style strings.
+//!
+//! This module is an implementation detail of the macros and is not part of
+//! the public API.
+
+use std::borrow::Cow;
+
+use crate::style::ParseStyleErrorKind as ErrorKind;
+use crate::theme;
+use crate::{Attribute, Attributes, Color, ColorDepth, Style};
+
+/// The largest number of bytes the color code(s) of a style may take up.
+const CODES_CAPACITY: usize = 64;
+
+/// The largest number of bytes an error message may take up.
+const MESSAGE_CAPACITY: usize = 256;
+
+/// The largest number of bytes an error message for invalid markup may take
+/// up.
+const MARKUP_MESSAGE_CAPACITY: usize = 512;
+
+/// The largest number of bytes of the input that an error message echoes.
+/// Longer input is cut off so that the message fits in its capacity.
+const MAX_ECHO_LEN: usize = 48;
+
+/// The longest token for which a suggestion is looked up.
+const MAX_SUGGESTION_LEN: usize = 32;
+
+/// The basic color names in the order of their ANSI color code offsets.
+pub const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// The words that are suggested for a misspelled token, along with the
+/// suggestion for each. Common color names that are not valid are mapped to
+/// the closest valid color.
+const SUGGESTIONS: [(&str, &str); 29] = [
+    ("black", "black"),
+    ("red", "red"),
+    ("green", "green"),
+    ("yellow", "yellow"),
+    ("blue", "blue"),
+    ("magenta", "magenta"),
+    ("cyan", "cyan"),
+    ("white", "white"),
+    ("bright", "bright"),
+    ("current", "current"),
+    ("reset", "reset"),
+    ("on", "on"),
+    ("bold", "bold"),
+    ("dim", "dim"),
+    ("italic", "italic"),
+    ("underline", "underline"),
+    ("blink", "blink"),
+    ("reverse", "reverse"),
+    ("hidden", "hidden"),
+    ("strikethrough", "strikethrough"),
+    ("faint", "dim"),
+    ("underlined", "underline"),
+    ("inverse", "reverse"),
+    ("strike", "strikethrough"),
+    ("purple", "magenta"),
+    ("violet", "magenta"),
+    ("aqua", "cyan"),
+    ("gray", "bright black"),
+    ("grey", "bright black"),
+];
+
+/// A fixed-capacity string that can be built up in a const context.
+#[derive(Clone, Copy)]
+pub struct ConstStr<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ConstStr<N> {
+    /// Returns a new, empty `ConstStr`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { bytes: [0; N], len: 0 }
+    }
+
+    /// Appends `s` to the end of this string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity of the string would be exceeded.
+    #[must_use]
+    pub const fn push_str(mut self, s: &str) -> Self {
+        let s = s.as_bytes();
+        assert!(self.len + s.len() <= N, "style string is too long");
+
+        let mut i = 0;
+        while i < s.len() {
+            self.bytes[self.len] = s[i];
+            self.len += 1;
+            i += 1;
+        }
+        self
+    }
+
+    /// Appends at most `max_len` bytes of `s` to the end of this string,
+    /// followed by "..." if `s` is cut off.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity of the string would be exceeded.
+    #[must_use]
+    pub const fn push_truncated(self, s: &str, max_len: usize) -> Self {
+        if s.len() <= max_len {
+            return self.push_str(s);
+        }
+
+        let mut end = max_len;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.push_str(s.split_at(end).0).push_str("...")
+    }
+
+    /// Appends the decimal representation of `num` to the end of this string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity of the string would be exceeded.
+    #[must_use]
+    pub const fn push_usize(self, mut num: usize) -> Self {
+        const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+        let mut divisor = 1;
+        while num / divisor >= 10 {
+            divisor *= 10;
+        }
+
+        let mut this = self;
+        while divisor > 0 {
+            this = this.push_str(DIGITS[num / divisor]);
+            num %= divisor;
+            divisor /= 10;
+        }
+        this
+    }
+
+    /// Appends the decimal representation of `num` to the end of this string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity of the string would be exceeded.
+    #[must_use]
+    pub const fn push_u8(self, num: u8) -> Self {
+        self.push_usize(num as usize)
+    }
+
+    /// Returns true if this string is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the contents of this string as a `&str`.
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match core::str::from_utf8(self.bytes.split_at(self.len).0) {
+            Ok(s) => s,
+            Err(_) => panic!("a `ConstStr` always contains valid UTF-8"),
+        }
+    }
+}
+
+impl<const N: usize> Default for ConstStr<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The color code(s) of a style string, e.g. "91;44m".
+pub type Codes = ConstStr<CODES_CAPACITY>;
+
+/// An error that occurred while parsing a style string, along with the byte
+/// range of the offending token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The kind of error.
+    pub kind: ErrorKind,
+    /// The byte offset of the start of the offending token.
+    pub start: usize,
+    /// The byte offset of the end of the offending token.
+    pub end: usize,
+}
+
+impl Error {
+    const fn new(kind: ErrorKind, token: Token<'_>) -> Self {
+        Self { kind, start: token.start, end: token.end }
+    }
+
+    /// Returns the offending token in `style`.
+    #[must_use]
+    pub const fn token(self, style: &str) -> &str {
+        let (_, rest) = style.split_at(self.start);
+        rest.split_at(self.end - self.start).0
+    }
+
+    /// Returns a suggestion for the offending token in `style`.
+    #[must_use]
+    pub const fn suggestion(self, style: &str) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::UnknownToken => suggest(self.token(style)),
+            _ => None,
+        }
+    }
+}
+
+/// A single whitespace-separated token of a style string.
+#[derive(Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Returns the next token of `style` that starts at or after `pos`.
+const fn next_token(style: &str, pos: usize) -> Option<Token<'_>> {
+    let bytes = style.as_bytes();
+
+    let mut start = pos;
+    while start < bytes.len() && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+
+    if start == bytes.len() {
+        return None;
+    }
+
+    let mut end = start;
+    let mut in_parens = false;
+    while end < bytes.len() && (in_parens || !bytes[end].is_ascii_whitespace()) {
+        // Whitespace is allowed between parentheses, e.g. "rgb(1, 2, 3)".
+        match bytes[end] {
+            b'(' => in_parens = true,
+            b')' => in_parens = false,
+            _ => {}
+        }
+        end += 1;
+    }
+
+    // Tokens are split on ASCII whitespace so these are char boundaries.
+    let (_, rest) = style.split_at(start);
+    let (text, _) = rest.split_at(end - start);
+    Some(Token { text, start, end })
+}
+
+/// Compares two strings for equality, ignoring ASCII case.
+const fn eq_ignore_case(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if !a[i].eq_ignore_ascii_case(&b[i]) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Returns the color with the given name, or `None` if the name is not a
+/// color.
+const fn color_name(name: &str) -> Option<Color> {
+    if eq_ignore_case(name, "current") {
+        return Some(Color::Current);
+    }
+
+    if eq_ignore_case(name, "reset") {
+        return Some(Color::Reset);
+    }
+
+    let mut i = 0;
+    while i < COLOR_NAMES.len() {
+        if eq_ignore_case(name, COLOR_NAMES[i]) {
+            return Some(Color::BASIC[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns the optimal string alignment distance between two strings,
+/// ignoring ASCII case, or `None` if `a` is too long to compare.
+const fn edit_distance(a: &str, b: &str) -> Option<usize> {
+    const LEN: usize = MAX_SUGGESTION_LEN + 1;
+
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+
+    if a.len() > MAX_SUGGESTION_LEN || b.len() > MAX_SUGGESTION_LEN {
+        return None;
+    }
+
+    // The previous two rows and the current row of the distance matrix.
+    let mut prev2 = [0; LEN];
+    let mut prev = [0; LEN];
+    let mut row = [0; LEN];
+
+    let mut j = 0;
+    while j <= b.len() {
+        prev[j] = j;
+        j += 1;
+    }
+
+    let mut i = 1;
+    while i <= a.len() {
+        row[0] = i;
+
+        let mut j = 1;
+        while j <= b.len() {
+            let cost = if a[i - 1].eq_ignore_ascii_case(&b[j - 1]) { 0 } else { 1 };
+
+            let mut dist = prev[j] + 1;
+            if row[j - 1] + 1 < dist {
+                dist = row[j - 1] + 1;
+            }
+            if prev[j - 1] + cost < dist {
+                dist = prev[j - 1] + cost;
+            }
+
+            let transposed = i > 1
+                && j > 1
+                && a[i - 1].eq_ignore_ascii_case(&b[j - 2])
+                && a[i - 2].eq_ignore_ascii_case(&b[j - 1]);
+            if transposed && prev2[j - 2] + 1 < dist {
+                dist = prev2[j - 2] + 1;
+            }
+
+            row[j] = dist;
+            j += 1;
+        }
+
+        prev2 = prev;
+        prev = row;
+        i += 1;
+    }
+    Some(prev[b.len()])
+}
+
+/// Returns the valid word that is closest to `token`, if any word is close
+/// enough to be a plausible correction.
+#[must_use]
+pub const fn suggest(token: &str) -> Option<&'static str> {
+    let max_dist = if token.len() < 6 { 1 } else { token.len() / 3 };
+
+    let mut best: Option<(usize, &str)> = None;
+
+    let mut i = 0;
+    while i < SUGGESTIONS.len() {
+        let (word, suggestion) = SUGGESTIONS[i];
+
+        if let Some(dist) = edit_distance(token, word) {
+            let closer = match best {
+                Some((best_dist, _)) => dist < best_dist,
+                None => true,
+            };
+
+            if dist <= max_dist && closer {
+                best = Some((dist, suggestion));
+            }
+        }
+        i += 1;
+    }
+
+    match best {
+        Some((_, suggestion)) => Some(suggestion),
+        None => None,
+    }
+}
+
+/// Parses one `[bright] color` color specification or color value (e.g.
+/// "#ff8800") that starts at `pos`,
+/// just after the `prev` token.
+///
+/// Returns the color and the position just after the color name.
+const fn parse_color_at(
+    style: &str,
+    pos: usize,
+    prev: Token<'_>,
+) -> Result<(Color, usize), Error> {
+    let Some(token) = next_token(style, pos) else {
+        return Err(Error::new(ErrorKind::MissingColor, prev));
+    };
+
+    let (bright, token) = if eq_ignore_case(token.text, "bright") {
+        match next_token(style, token.end) {
+            Some(name) => (true, name),
+            None => return Err(Error::new(ErrorKind::MissingColor, token)),
+        }
+    } else {
+        (false, token)
+    };
+
+    if let Some(color) = color_value(token.text) {
+        // Only the basic color names have bright variants.
+        if bright {
+            return Err(Error::new(ErrorKind::UnexpectedToken, token));
+        }
+        return match color {
+            Ok(color) => Ok((color, token.end)),
+            Err(kind) => Err(Error::new(kind, token)),
+        };
+    }
+
+    match color_name(token.text) {
+        Some(color) if bright => Ok((color.to_bright(), token.end)),
+        Some(color) => Ok((color, token.end)),
+        None => Err(Error::new(ErrorKind::UnknownToken, token)),
+    }
+}
+
+/// Returns an error if `style` contains another token at or after `pos`.
+const fn expect_end(style: &str, pos: usize) -> Result<(), Error> {
+    match next_token(style, pos) {
+        Some(token) => Err(Error::new(ErrorKind::UnexpectedToken, token)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the first token of `style` or an error if `style` is empty.
+const fn first_token(style: &str) -> Result<Token<'_>, Error> {
+    match next_token(style, 0) {
+        Some(token) => Ok(token),
+        None => Err(Error { kind: ErrorKind::Empty, start: 0, end: 0 }),
+    }
+}
+
+/// Parses a `[bright] color` color name.
+///
+/// # Errors
+///
+/// Returns an error describing the offending token if `s` is not a color
+/// name.
+pub const fn parse_color(s: &str) -> Result<Color, Error> {
+    let first = match first_token(s) {
+        Ok(first) => first,
+        Err(e) => return Err(e),
+    };
+
+    let empty = Token { text: "", start: first.start, end: first.start };
+
+    match parse_color_at(s, 0, empty) {
+        Ok((color, pos)) => match expect_end(s, pos) {
+            Ok(()) => Ok(color),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns `s` without `prefix` if `s` starts with `prefix`, ignoring ASCII
+/// case.
+const fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.len() < prefix.len() || !s.is_char_boundary(prefix.len()) {
+        return None;
+    }
+
+    let (head, rest) = s.split_at(prefix.len());
+    if eq_ignore_case(head, prefix) {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+/// Returns the arguments of a functional notation color such as
+/// "rgb(1, 2, 3)" if `s` is a call of the function `name`.
+const fn call_args<'a>(s: &'a str, name: &str) -> Option<&'a str> {
+    let Some(rest) = strip_prefix(s, name) else {
+        return None;
+    };
+
+    let Some(args) = strip_prefix(rest.trim_ascii_start(), "(") else {
+        return None;
+    };
+
+    match args.as_bytes() {
+        [.., b')'] => Some(args.split_at(args.len() - 1).0),
+        _ => None,
+    }
+}
+
+/// Parses a decimal number from 0 to 255, ignoring surrounding whitespace.
+const fn parse_u8(s: &str) -> Option<u8> {
+    let digits = s.trim_ascii().as_bytes();
+
+    if digits.is_empty() || digits.len() > 3 {
+        return None;
+    }
+
+    let mut num: u16 = 0;
+    let mut i = 0;
+    while i < digits.len() {
+        if !digits[i].is_ascii_digit() {
+            return None;
+        }
+        num = num * 10 + (digits[i] - b'0') as u16;
+        i += 1;
+    }
+
+    if num > 255 {
+        None
+    } else {
+        #[allow(clippy::cast_possible_truncation)]
+        Some(num as u8)
+    }
+}
+
+/// Parses the three comma-separated channels of an "rgb(r, g, b)" color.
+const fn parse_rgb_args(args: &str) -> Option<Color> {
+    let mut channels = [0; 3];
+    let mut rest = args;
+    let mut i = 0;
+
+    while i < channels.len() {
+        let bytes = rest.as_bytes();
+
+        let mut end = 0;
+        while end < bytes.len() && bytes[end] != b',' {
+            end += 1;
+        }
+
+        // The last channel must not be followed by a comma and the others
+        // must be.
+        if (i == channels.len() - 1) != (end == bytes.len()) {
+            return None;
+        }
+
+        let (channel, remaining) = rest.split_at(end);
+        channels[i] = match parse_u8(channel) {
+            Some(channel) => channel,
+            None => return None,
+        };
+
+        rest = if remaining.is_empty() {
+            remaining
+        } else {
+            remaining.split_at(1).1
+        };
+        i += 1;
+    }
+
+    Some(Color::Rgb(channels[0], channels[1], channels[2]))
+}
+
+/// Returns the value of a hexadecimal digit.
+const fn hex_digit(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Parses the digits of a "#rrggbb" or "#rgb" hex color.
+const fn parse_hex(digits: &str) -> Option<Color> {
+    let digits = digits.as_bytes();
+
+    let mut values = [0; 6];
+    let mut i = 0;
+    while i < digits.len() && i < values.len() {
+        values[i] = match hex_digit(digits[i]) {
+            Some(value) => value,
+            None => return None,
+        };
+        i += 1;
+    }
+
+    match digits.len() {
+        3 => Some(Color::Rgb(values[0] * 17, values[1] * 17, values[2] * 17)),
+        6 => Some(Color::Rgb(
+            values[0] * 16 + values[1],
+            values[2] * 16 + values[3],
+            values[4] * 16 + values[5],
+        )),
+        _ => None,
+    }
+}
+
+/// Parses a hex ("#ff8800" or "#f80"), RGB ("rgb(255, 136, 0)") or 256-color
+/// ("208", "256:208" or "color(208)") color value.
+///
+/// Returns `None` if `s` is not written in one of these notations at all, and
+/// an error if it is but the value is invalid (e.g. "#ff88").
+const fn color_value(s: &str) -> Option<Result<Color, ErrorKind>> {
+    let color = if let Some(digits) = strip_prefix(s, "#") {
+        parse_hex(digits)
+    } else if let Some(args) = call_args(s, "rgb") {
+        parse_rgb_args(args)
+    } else if let Some(num) = call_args(s, "color") {
+        color256(num)
+    } else if let Some(num) = strip_prefix(s, "256:") {
+        color256(num)
+    } else if !s.is_empty() && s.as_bytes()[0].is_ascii_digit() {
+        color256(s)
+    } else if strip_prefix(s, "rgb(").is_some() || strip_prefix(s, "color(").is_some() {
+        // An unclosed functional notation color.
+        None
+    } else {
+        return None;
+    };
+
+    match color {
+        Some(color) => Some(Ok(color)),
+        None => Some(Err(ErrorKind::InvalidColorValue)),
+    }
+}
+
+/// Parses the color number of a 256-color mode color.
+const fn color256(num: &str) -> Option<Color> {
+    match parse_u8(num) {
+        Some(num) => Some(Color::Color256(num)),
+        None => None,
+    }
+}
+
+/// Returns the attribute with the given name, or `None` if the name is not an
+/// attribute.
+const fn attribute_name(name: &str) -> Option<Attribute> {
+    let mut i = 0;
+    while i < Attribute::ALL.len() {
+        if eq_ignore_case(name, Attribute::ALL[i].name()) {
+            return Some(Attribute::ALL[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Converts a parsed color into the color of a `Style`, where the current
+/// color is represented by `None`.
+const fn style_color(color: Color) -> Option<Color> {
+    match color {
+        Color::Current => None,
+        color => Some(color),
+    }
+}
+
+/// Parses an "X on Y" style string, where X and Y are color names or color
+/// values and any number of attributes (e.g. "bold") may appear anywhere in
+/// the string.
+///
+/// # Errors
+///
+/// Returns an error describing the offending token if `style` is not a valid
+/// style string.
+pub const fn parse_style(style: &str) -> Result<Style, Error> {
+    if let Err(e) = first_token(style) {
+        return Err(e);
+    }
+
+    let mut parsed = Style::new();
+    let mut found_foreground = false;
+    let mut found_background = false;
+    let mut pos = 0;
+
+    while let Some(token) = next_token(style, pos) {
+        if let Some(attr) = attribute_name(token.text) {
+            parsed.attrs = parsed.attrs.with(attr);
+            pos = token.end;
+        } else if eq_ignore_case(token.text, "on") {
+            if found_background {
+                return Err(Error::new(ErrorKind::UnexpectedToken, token));
+            }
+
+            match parse_color_at(style, token.end, token) {
+                Ok((color, end)) => {
+                    parsed.bg = style_color(color);
+                    found_background = true;
+                    pos = end;
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            match parse_color_at(style, token.start, token) {
+                // The foreground color must come before the background color.
+                Ok(_) if found_foreground || found_background => {
+                    return Err(Error::new(ErrorKind::UnexpectedToken, token));
+                }
+                Ok((color, end)) => {
+                    parsed.fg = style_color(color);
+                    found_foreground = true;
+                    pos = end;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Appends the non-empty ANSI code(s) in `part` to `codes`, separated by a
+/// semicolon.
+const fn join_codes(codes: Codes, part: Codes) -> Codes {
+    if part.is_empty() {
+        codes
+    } else if codes.is_empty() {
+        part
+    } else {
+        codes.push_str(";").push_str(part.as_str())
+    }
+}
+
+/// Returns the ANSI code(s) of `style` followed by the terminating 'm',
+/// e.g. "1;91;44m".
+#[must_use]
+pub const fn codes(style: &Style) -> Codes {
+    let mut codes = style.attrs.push_ansi_codes(Codes::new());
+
+    if let Some(fg) = style.fg {
+        codes = join_codes(codes, fg.push_ansi_code(Codes::new(), false));
+    }
+
+    if let Some(bg) = style.bg {
+        codes = join_codes(codes, bg.push_ansi_code(Codes::new(), true));
+    }
+
+    codes.push_str("m")
+}
+
+/// Returns the ANSI code(s) of a foreground and a background color followed
+/// by the terminating 'm', e.g. "38;5;196m".
+#[must_use]
+pub const fn color_codes(fg: Option<Color>, bg: Option<Color>) -> Codes {
+    codes(&Style { fg, bg, attrs: Attributes::new() })
+}
+
+/// Returns the error message for an invalid style string.
+const fn error_message(style: &str, err: Error) -> ConstStr<MESSAGE_CAPACITY> {
+    let message = ConstStr::new()
+        .push_str("invalid style string \"")
+        .push_truncated(style, MAX_ECHO_LEN)
+        .push_str("\": ");
+
+    push_error(message, style, err)
+}
+
+/// Appends the description of the error `err` in `style` to `message`.
+const fn push_error<const N: usize>(message: ConstStr<N>, style: &str, err: Error) -> ConstStr<N> {
+    let message = message.push_str(err.kind.description());
+
+    if matches!(err.kind, ErrorKind::Empty) {
+        return message;
+    }
+
+    let message = message
+        .push_str(" \"")
+        .push_truncated(err.token(style), MAX_ECHO_LEN)
+        .push_str("\" at position ")
+        .push_usize(err.start);
+
+    match err.suggestion(style) {
+        Some(suggestion) => message
+            .push_str("; did you mean \"")
+            .push_str(suggestion)
+            .push_str("\"?"),
+        None => message,
+    }
+}
+
+/// Parses an "X on Y" style string and returns its ANSI color code(s)
+/// followed by the terminating 'm', e.g. "91;44m".
+///
+/// # Panics
+///
+/// Panics, which fails compilation when called in a const context, if the
+/// style string is not valid.
+#[must_use]
+pub const fn style_codes(style: &str) -> Codes {
+    match parse_style(style) {
+        Ok(parsed) => codes(&parsed),
+        Err(e) => panic!("{}", error_message(style, e).as_str()),
+    }
+}
+
+/// Returns true if `style` can name a style of a `Theme`, i.e. it is a single
+/// word of ASCII letters, digits, '-', '_' and '.'.
+///
+/// The theme is only known at runtime, so a word that is close to a built-in
+/// name (e.g. "ok" or "gold") may be a theme name as well as a typo.
+const fn is_theme_name(style: &str) -> bool {
+    let name = style.trim_ascii().as_bytes();
+
+    let mut i = 0;
+    while i < name.len() {
+        if !(name[i].is_ascii_alphanumeric() || matches!(name[i], b'-' | b'_' | b'.')) {
+            return false;
+        }
+        i += 1;
+    }
+    !name.is_empty()
+}
+
+/// Parses a literal style string of `parse_colors!()` and returns its ANSI
+/// color code(s) followed by the terminating 'm', e.g. "91;44m".
+///
+/// A single word that is not a valid style string is the name of a style of
+/// the theme, which is only known at runtime, so it has no codes (and the
+/// text is written without escape codes) until `themed_codes` looks it up.
+///
+/// # Panics
+///
+/// Panics, which fails compilation when called in a const context, if the
+/// style string is neither valid nor a theme name.
+#[must_use]
+pub const fn literal_codes(style: &str) -> Codes {
+    match parse_style(style) {
+        Ok(parsed) => codes(&parsed),
+        Err(_) if is_theme_name(style) => Codes::new(),
+        Err(e) => panic!("{}", error_message(style, e).as_str()),
+    }
+}
+
+/// Returns the position just after the format string placeholder (e.g.
+/// "{:>8}") that starts at `pos`.
+const fn skip_placeholder(bytes: &[u8], pos: usize) -> usize {
+    let mut end = pos + 1;
+    while end < bytes.len() && bytes[end] != b'}' {
+        end += 1;
+    }
+    end + 1
+}
+
+/// Returns the position of the first `>` at or after `pos` that is not part
+/// of a placeholder (e.g. "{:>8}"), if any.
+const fn find_tag_end(bytes: &[u8], pos: usize) -> Option<usize> {
+    let mut end = pos;
+    while end < bytes.len() {
+        match bytes[end] {
+            b'>' => return Some(end),
+            b'{' | b'}' if end + 1 < bytes.len() && bytes[end + 1] == bytes[end] => end += 2,
+            b'{' => end = skip_placeholder(bytes, end),
+            _ => end += 1,
+        }
+    }
+    None
+}
+
+/// Returns the error message for invalid markup at `pos`.
+const fn markup_message(description: &str, pos: usize) -> ConstStr<MARKUP_MESSAGE_CAPACITY> {
+    ConstStr::new()
+        .push_str("invalid markup: ")
+        .push_str(description)
+        .push_str(" at position ")
+        .push_usize(pos)
+}
+
+/// Validates the markup in the literal format string of a `x_markup!()`
+/// macro.
+///
+/// Every tag must be terminated, closed and contain a valid style string or
+/// theme name. Placeholders such as "{:<8}" are skipped, and the style of a
+/// tag containing a placeholder is only known at runtime.
+///
+/// # Panics
+///
+/// Panics, which fails compilation when called in a const context, if the
+/// markup is not valid.
+pub const fn check_markup(markup: &str) {
+    let bytes = markup.as_bytes();
+    // The positions of the outermost open tags.
+    let mut open = [0; 16];
+    let mut depth = 0;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'{' | b'}' if pos + 1 < bytes.len() && bytes[pos + 1] == bytes[pos] => pos += 2,
+            b'{' => pos = skip_placeholder(bytes, pos),
+            b'<' if pos + 1 < bytes.len() && bytes[pos + 1] == b'<' => pos += 2,
+            b'<' => {
+                let Some(end) = find_tag_end(bytes, pos + 1) else {
+                    let message = markup_message("unterminated tag", pos);
+                    panic!("{}", message.push_str("; write \"<<\" for a literal \"<\"").as_str());
+                };
+
+                // Tags are delimited by ASCII characters, so these are char
+                // boundaries.
+                let tag = markup.split_at(end).0.split_at(pos + 1).1;
+
+                if eq_ignore_case(tag, "/") {
+                    if depth == 0 {
+                        let message = markup_message("closing tag \"</>\"", pos);
+                        panic!("{}", message.push_str(" has no matching opening tag").as_str());
+                    }
+                    depth -= 1;
+                } else {
+                    // A theme name is only known at runtime.
+                    if !contains_byte(tag, b'{') && !is_theme_name(tag) {
+                        if let Err(e) = parse_style(tag) {
+                            let message = markup_message("invalid style in tag", pos)
+                                .push_str(": \"")
+                                .push_truncated(tag, MAX_ECHO_LEN)
+                                .push_str("\": ");
+                            panic!("{}", push_error(message, tag, e).as_str());
+                        }
+                    }
+                    if depth < open.len() {
+                        open[depth] = pos;
+                    }
+                    depth += 1;
+                }
+                pos = end + 1;
+            }
+            _ => pos += 1,
+        }
+    }
+
+    if depth > 0 {
+        let pos = open[if depth < open.len() { depth - 1 } else { open.len() - 1 }];
+        panic!("{}", markup_message("unclosed tag", pos).as_str());
+    }
+}
+
+/// Returns true if `s` contains the byte `byte`.
+const fn contains_byte(s: &str, byte: u8) -> bool {
+    let bytes = s.as_bytes();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == byte {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Returns the ANSI color code(s) of the style that the literal style string
+/// `style` names in the process-wide theme, or its precomputed code(s)
+/// `codes` if the theme has no style by that name.
+///
+/// A single word, including a built-in name such as "red", takes the theme's
+/// read lock for the lookup; style strings of several words skip it.
+#[must_use]
+pub fn themed_codes(style: &str, codes: &'static str) -> Cow<'static, str> {
+    theme::lookup(style).map_or(Cow::Borrowed(codes), |themed| {
+        Cow::Owned(self::codes(&themed).as_str().to_string())
+    })
+}
+
+/// A style argument of the `x_styled!()` macros that is not a string
+/// literal, i.e. a runtime style string or a `Style`.
+pub trait StyleArg {
+    /// Returns the ANSI code(s) of this style followed by the terminating
+    /// 'm', e.g. "91;44m".
+    fn ansi_codes(&self) -> String;
+}
+
+impl StyleArg for str {
+    /// Looks up a single word in the process-wide theme, or parses an "X on
+    /// Y" style string at runtime. An invalid style string has no codes, so
+    /// the text is written without escape codes.
+    fn ansi_codes(&self) -> String {
+        theme::lookup(self)
+            .or_else(|| parse_style(self).ok())
+            .map_or_else(String::new, |style| codes(&style).as_str().to_string())
+    }
+}
+
+impl StyleArg for String {
+    fn ansi_codes(&self) -> String {
+        self.as_str().ansi_codes()
+    }
+}
+
+impl StyleArg for Style {
+    fn ansi_codes(&self) -> String {
+        codes(self).as_str().to_string()
+    }
+}
+
+impl<T: StyleArg + ?Sized> StyleArg for &T {
+    fn ansi_codes(&self) -> String {
+        (**self).ansi_codes()
+    }
+}
+
+/// Returns the ANSI color code(s) of a runtime style string or a `Style`
+/// followed by the terminating 'm', e.g. "91;44m".
+///
+/// An invalid style string leaves the current colors unchanged.
+#[must_use]
+pub fn runtime_codes<S: StyleArg + ?Sized>(style: &S) -> String {
+    style.ansi_codes()
+}
+
+/// Returns the color set by the SGR parameters at the start of `params` (e.g.
+/// "38", "5" and "208"), whether it is a background color, and the number of
+/// parameters that set it.
+fn sgr_color(params: &[&str]) -> Option<(Color, bool, usize)> {
+    let number = |idx: usize| params.get(idx).and_then(|param| param.parse::<u8>().ok());
+
+    match number(0)? {
+        code @ 30..=37 => Some((Color::BASIC[usize::from(code - 30)], false, 1)),
+        code @ 40..=47 => Some((Color::BASIC[usize::from(code - 40)], true, 1)),
+        code @ 90..=97 => Some((Color::BRIGHT[usize::from(code - 90)], false, 1)),
+        code @ 100..=107 => Some((Color::BRIGHT[usize::from(code - 100)], true, 1)),
+        code @ (38 | 48) => match number(1)? {
+            5 => Some((Color::Color256(number(2)?), code == 48, 3)),
+            2 => Some((Color::Rgb(number(2)?, number(3)?, number(4)?), code == 48, 5)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the ANSI code(s) `codes` (e.g. "1;38;2;255;0;0m") with every
+/// color that a terminal with the color depth `depth` does not support
+/// replaced by the closest color it does support.
+#[must_use]
+pub fn downgrade_codes(codes: &str, depth: ColorDepth) -> Cow<'_, str> {
+    if depth == ColorDepth::TrueColor || codes.is_empty() {
+        return Cow::Borrowed(codes);
+    }
+
+    let params: Vec<&str> = codes.strip_suffix('m').unwrap_or(codes).split(';').collect();
+    let mut downgraded = String::with_capacity(codes.len());
+    let mut idx = 0;
+
+    while idx < params.len() {
+        let code = if let Some((color, background, len)) = sgr_color(&params[idx..]) {
+            idx += len;
+            color
+                .to_depth(depth)
+                .push_ansi_code(Codes::new(), background)
+                .as_str()
+                .to_string()
+        } else {
+            idx += 1;
+            params[idx - 1].to_string()
+        };
+
+        if !code.is_empty() {
+            if !downgraded.is_empty() {
+                downgraded.push(';');
+            }
+            downgraded.push_str(&code);
+        }
+    }
+
+    downgraded.push('m');
+    Cow::Owned(downgraded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_markup, downgrade_codes, edit_distance, literal_codes, style_codes, suggest,
+        themed_codes,
+    };
+    use crate::ColorDepth;
+
+    #[test]
+    fn test_style_codes() {
+        assert_eq!(style_codes("red").as_str(), "31m");
+        assert_eq!(style_codes("on red").as_str(), "41m");
+        assert_eq!(style_codes("bright red on bright white").as_str(), "91;107m");
+        assert_eq!(style_codes("current on reset").as_str(), "0m");
+        assert_eq!(style_codes("reset on red").as_str(), "0;41m");
+        assert_eq!(style_codes("bright current").as_str(), "m");
+        assert_eq!(style_codes("bold").as_str(), "1m");
+        assert_eq!(
+            style_codes("bold underline bright yellow on blue").as_str(),
+            "1;4;93;44m"
+        );
+        assert_eq!(style_codes("italic on red dim").as_str(), "2;3;41m");
+        assert_eq!(style_codes("STRIKETHROUGH current on current").as_str(), "9m");
+    }
+
+    #[test]
+    fn test_style_codes_color_values() {
+        assert_eq!(style_codes("#ff8800 on 236").as_str(), "38;2;255;136;0;48;5;236m");
+        assert_eq!(style_codes("#F80").as_str(), "38;2;255;136;0m");
+        assert_eq!(style_codes("on rgb(255,136,0)").as_str(), "48;2;255;136;0m");
+        assert_eq!(
+            style_codes("bold RGB( 1 , 2,3 ) on color(208)").as_str(),
+            "1;38;2;1;2;3;48;5;208m"
+        );
+        assert_eq!(style_codes("256:0 on bright red").as_str(), "38;5;0;101m");
+        assert_eq!(style_codes("red on #000000").as_str(), "31;48;2;0;0;0m");
+    }
+
+    #[test]
+    fn test_invalid_color_values() {
+        for style in [
+            "#ff88", "#ggg", "#", "256", "256:", "color()", "color(1", "rgb(1,2)",
+            "rgb(1,2,3,)", "rgb(1,2,3,4)", "rgb(256,0,0)", "1a", "on 999",
+        ] {
+            let err = super::parse_style(style).unwrap_err();
+            assert_eq!(err.kind, super::ErrorKind::InvalidColorValue, "{style}");
+        }
+    }
+
+    #[test]
+    fn test_non_ascii_tokens() {
+        for style in ["é", "on ü", "ärgb(1,2,3)", "red on 日本", "éred"] {
+            let err = super::parse_style(style).unwrap_err();
+            assert_eq!(err.kind, super::ErrorKind::UnknownToken, "{style}");
+        }
+        for style in ["#é", "#ffé", "256:é", "rgb(é)", "color(1é)", "rgb(1,2,é)"] {
+            let err = super::parse_style(style).unwrap_err();
+            assert_eq!(err.kind, super::ErrorKind::InvalidColorValue, "{style}");
+        }
+    }
+
+    #[test]
+    fn test_bright_color_values() {
+        for (style, token) in [
+            ("bright #ff0000", "#ff0000"),
+            ("bright 256:3", "256:3"),
+            ("red on bright rgb(1, 2, 3)", "rgb(1, 2, 3)"),
+            ("bright color(9)", "color(9)"),
+        ] {
+            let err = super::parse_style(style).unwrap_err();
+            assert_eq!(err.kind, super::ErrorKind::UnexpectedToken, "{style}");
+            assert_eq!(err.token(style), token);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected token \"#ff0000\" at position 7")]
+    fn test_style_codes_bright_color_value() {
+        let _ = style_codes("bright #ff0000");
+    }
+
+    #[test]
+    #[should_panic(expected = "bold bol...\": unknown color or attribute name \"purpel\" at \
+                               position 300; did you mean \"magenta\"?")]
+    fn test_style_codes_long_style_string() {
+        let _ = style_codes(&format!("{}purpel", "bold ".repeat(60)));
+    }
+
+    #[test]
+    fn test_error_message_truncates_long_tokens() {
+        let style = format!("red on {}", "é".repeat(40));
+        let err = super::parse_style(&style).unwrap_err();
+        let message = super::error_message(&style, err);
+
+        let echoed = format!("red on {}", "é".repeat(20));
+        let token = "é".repeat(24);
+        assert_eq!(
+            message.as_str(),
+            format!(
+                "invalid style string \"{echoed}...\": unknown color or attribute name \
+                 \"{token}...\" at position 7"
+            )
+        );
+    }
+
+    #[test]
+    fn test_style_codes_ignore_case_and_extra_whitespace() {
+        assert_eq!(style_codes("  BriGHT   red\ton Blue ").as_str(), "91;44m");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "unknown color or attribute name \"brigth\" at position 0; did you mean \"bright\"?"
+    )]
+    fn test_style_codes_unknown_color() {
+        let _ = style_codes("brigth red");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a color after \"on\" at position 4")]
+    fn test_style_codes_missing_background() {
+        let _ = style_codes("red on");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected token \"blue\" at position 4")]
+    fn test_style_codes_missing_on() {
+        let _ = style_codes("red blue");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected token \"red\" at position 8")]
+    fn test_style_codes_foreground_after_background() {
+        let _ = style_codes("on blue red");
+    }
+
+    #[test]
+    fn test_literal_codes_theme_names() {
+        assert_eq!(literal_codes("bold red").as_str(), "1;31m");
+        assert_eq!(literal_codes(" my-theme.name_2 ").as_str(), "");
+        assert_eq!(themed_codes("error", ""), "1;31m");
+        assert_eq!(themed_codes("red", "31m"), "31m");
+        assert_eq!(themed_codes("not-in-theme", ""), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid color value \"rgb(1,\" at position 0")]
+    fn test_literal_codes_invalid_word() {
+        let _ = literal_codes("rgb(1,");
+    }
+
+    #[test]
+    fn test_literal_codes_names_close_to_builtin_names() {
+        // These may be theme names, which are only known at runtime.
+        for name in ["ok", "gold", "link", "purpel"] {
+            assert_eq!(literal_codes(name).as_str(), "", "{name}");
+        }
+    }
+
+    #[test]
+    fn test_check_markup_theme_names() {
+        check_markup("<error>a</> <my-theme.name_2>b</> <bold {}>c</> <ok>d</>");
+    }
+
+    #[test]
+    #[allow(clippy::literal_string_with_formatting_args)]
+    fn test_check_markup_placeholders_in_tags() {
+        check_markup("<{:>8}>a</> <bold {:>}>b</>");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid style in tag at position 2: \"redd on\"")]
+    fn test_check_markup_invalid_style() {
+        check_markup("a <redd on>b</>");
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(edit_distance("purpel", "purple"), Some(1));
+        assert_eq!(edit_distance("kitten", "SITTING"), Some(3));
+        assert_eq!(suggest("purpel"), Some("magenta"));
+        assert_eq!(suggest("Gray"), Some("bright black"));
+        assert_eq!(suggest("blu"), Some("blue"));
+        assert_eq!(suggest("xyzzy"), None);
+        assert_eq!(suggest("underlne"), Some("underline"));
+        assert_eq!(suggest("faint"), Some("dim"));
+    }
+
+    #[test]
+    fn test_downgrade_codes() {
+        let codes = "1;38;2;255;0;0;48;5;236m";
+
+        assert_eq!(downgrade_codes(codes, ColorDepth::TrueColor), codes);
+        assert_eq!(downgrade_codes(codes, ColorDepth::Ansi256), "1;38;5;196;48;5;236m");
+        assert_eq!(downgrade_codes(codes, ColorDepth::Ansi16), "1;91;40m");
+        assert_eq!(downgrade_codes(codes, ColorDepth::None), "1m");
+        assert_eq!(downgrade_codes("31;104m", ColorDepth::Ansi16), "31;104m");
+        assert_eq!(downgrade_codes("31;104m", ColorDepth::None), "m");
+        assert_eq!(downgrade_codes("0m", ColorDepth::None), "0m");
+        assert_eq!(downgrade_codes("m", ColorDepth::Ansi16), "m");
+    }
+}
@@ -0,0 +1,304 @@
+//! Helpers for the macros that write to buffers and print to stdout and
+//! stderr.
+//!
+//! This module is an implementation detail of the macros and is not part of
+//! the public API.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::process;
+
+use crate::{control, markup, parse, ColorDepth, Gradient};
+
+/// The standard stream that a print macro writes to.
+///
+/// Colors are enabled or disabled separately for each stream (see
+/// `stream_colors`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stream {
+    /// The standard output stream.
+    Stdout,
+    /// The standard error stream.
+    Stderr,
+}
+
+impl Stream {
+    /// Returns true if the stream is connected to a terminal.
+    #[must_use]
+    pub fn is_terminal(self) -> bool {
+        match self {
+            Self::Stdout => io::stdout().is_terminal(),
+            Self::Stderr => io::stderr().is_terminal(),
+        }
+    }
+}
+
+/// Writes `args` styled with the ANSI code(s) `codes` to `stream` and
+/// flushes it, appending a newline if `newline` is true.
+///
+/// The text is written without any escape codes when color output is
+/// disabled for `stream`, and colors that the terminal does not support are
+/// downgraded to its color depth.
+///
+/// # Errors
+///
+/// Returns an error if writing to or flushing the stream fails.
+pub fn print(
+    stream: Stream,
+    codes: &str,
+    args: fmt::Arguments<'_>,
+    newline: bool,
+) -> io::Result<()> {
+    let colored = control::colors_enabled(Some(stream));
+    let codes = if colored {
+        print_codes(codes, control::color_depth())
+    } else {
+        Cow::Borrowed(codes)
+    };
+
+    match stream {
+        Stream::Stdout => write_text(&mut io::stdout().lock(), &codes, args, newline, colored),
+        Stream::Stderr => write_text(&mut io::stderr().lock(), &codes, args, newline, colored),
+    }
+}
+
+/// Returns the ANSI code(s) `codes` downgraded to the color depth `depth`,
+/// or no codes if nothing is left of them, so that e.g. a color-only style
+/// is printed as plain text when colors are not supported.
+fn print_codes(codes: &str, depth: ColorDepth) -> Cow<'_, str> {
+    match parse::downgrade_codes(codes, depth) {
+        Cow::Owned(downgraded) if downgraded == "m" => Cow::Borrowed(""),
+        downgraded => downgraded,
+    }
+}
+
+/// Writes `args` to `out`, surrounded by the ANSI code(s) `codes` and a reset
+/// code if `colored` is true, and flushes it.
+fn write_text<W: Write>(
+    out: &mut W,
+    codes: &str,
+    args: fmt::Arguments<'_>,
+    newline: bool,
+    colored: bool,
+) -> io::Result<()> {
+    let end = if newline { "\n" } else { "" };
+
+    write!(out, "{}{end}", StyledArgs { codes, args, colored })?;
+    out.flush()
+}
+
+/// Formatting arguments surrounded by ANSI code(s) and a reset code when
+/// displayed, unless colors are disabled or there are no code(s), e.g. for
+/// an unknown theme name.
+pub struct StyledArgs<'a> {
+    codes: &'a str,
+    args: fmt::Arguments<'a>,
+    colored: bool,
+}
+
+impl fmt::Display for StyledArgs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.colored && !self.codes.is_empty() {
+            write!(f, "\u{001b}[{}{}\u{001b}[0m", self.codes, self.args)
+        } else {
+            f.write_fmt(self.args)
+        }
+    }
+}
+
+/// Returns the text of the `write_*` macros: `args` styled with the ANSI
+/// code(s) `codes`, or without any escape codes if the color choice disables
+/// colors.
+#[must_use]
+pub fn write_args<'a>(codes: &'a str, args: fmt::Arguments<'a>) -> StyledArgs<'a> {
+    StyledArgs { codes, args, colored: control::colors_enabled(None) }
+}
+
+/// Writes `args` with `gradient` applied to it to `stream` in a single write
+/// and flushes it, appending a newline if `newline` is true.
+///
+/// The text is written without any escape codes when color output is
+/// disabled for `stream`, and the colors are downgraded to the color depth of
+/// the terminal.
+///
+/// # Errors
+///
+/// Returns an error if writing to or flushing the stream fails.
+pub fn print_gradient(
+    stream: Stream,
+    gradient: &Gradient,
+    args: fmt::Arguments<'_>,
+    newline: bool,
+) -> io::Result<()> {
+    let depth = control::colors_enabled(Some(stream)).then(control::color_depth);
+    let out = render_args(args, newline, |out, text| gradient.render(out, text, depth));
+
+    write_all(stream, &out)
+}
+
+/// Returns the text of the `write_*_gradient` macros: `args` with `gradient`
+/// applied to it, or without any escape codes if the color choice disables
+/// colors.
+///
+/// Unlike `print_gradient`, the colors are not downgraded to the color depth
+/// of the terminal, since the buffer may not be written to it.
+#[must_use]
+pub fn write_gradient(gradient: &Gradient, args: fmt::Arguments<'_>) -> String {
+    let depth = control::colors_enabled(None).then_some(ColorDepth::TrueColor);
+    render_args(args, false, |out, text| gradient.render(out, text, depth))
+}
+
+/// Writes the markup `args` with the escape codes of its tags to `stream` in
+/// a single write and flushes it, appending a newline if `newline` is true.
+///
+/// The text is written without any escape codes when color output is
+/// disabled for `stream`, and the colors are downgraded to the color depth of
+/// the terminal.
+///
+/// # Errors
+///
+/// Returns an error if writing to or flushing the stream fails.
+pub fn print_markup(stream: Stream, args: fmt::Arguments<'_>, newline: bool) -> io::Result<()> {
+    let depth = control::colors_enabled(Some(stream)).then(control::color_depth);
+    let out = render_args(args, newline, |out, text| markup::render(out, text, depth));
+
+    write_all(stream, &out)
+}
+
+/// Returns the text of the `write_*_markup` macros: the markup `args` with
+/// the escape codes of its tags, or without any escape codes if the color
+/// choice disables colors.
+#[must_use]
+pub fn write_markup(args: fmt::Arguments<'_>) -> String {
+    let depth = control::colors_enabled(None).then_some(ColorDepth::TrueColor);
+    render_args(args, false, |out, text| markup::render(out, text, depth))
+}
+
+/// Returns the text of `args` rendered with `render` (e.g. with a gradient
+/// applied to it), followed by a newline if `newline` is true.
+fn render_args(
+    args: fmt::Arguments<'_>,
+    newline: bool,
+    render: impl FnOnce(&mut String, &str),
+) -> String {
+    let text = args.as_str().map_or_else(|| Cow::Owned(fmt::format(args)), Cow::Borrowed);
+    let mut out = String::with_capacity(text.len() * 2 + 1);
+
+    render(&mut out, &text);
+    if newline {
+        out.push('\n');
+    }
+    out
+}
+
+/// Writes `out` to `stream` in a single write and flushes it.
+fn write_all(stream: Stream, out: &str) -> io::Result<()> {
+    fn write<W: Write>(mut stream: W, out: &str) -> io::Result<()> {
+        stream.write_all(out.as_bytes())?;
+        stream.flush()
+    }
+
+    match stream {
+        Stream::Stdout => write(io::stdout().lock(), out),
+        Stream::Stderr => write(io::stderr().lock(), out),
+    }
+}
+
+/// Handles the result of writing to stdout or stderr in the print macros.
+///
+/// A `BrokenPipe` error means that the reading end of the stream was closed
+/// (e.g. when the output is piped into `head`), so nothing more can be
+/// printed and the process exits quietly with status 0, the same as if it
+/// had been killed by `SIGPIPE` but without an error from the shell. This
+/// also ends loops that print forever. Any other error panics with the same
+/// message as `print!()` and `eprint!()`.
+///
+/// # Panics
+///
+/// Panics if `result` is an error other than `BrokenPipe`.
+pub fn handle_print_result(result: io::Result<()>, stream: &str) {
+    if let Err(e) = result {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            process::exit(0);
+        }
+        panic!("failed printing to {stream}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io;
+    use std::process::{Command, Stdio};
+
+    use super::{handle_print_result, print_codes, render_args, write_text};
+    use crate::{Color, ColorDepth, Gradient};
+
+    #[test]
+    fn test_write_text() {
+        let mut out = Vec::new();
+
+        write_text(&mut out, "31m", format_args!("{}", 1), false, true).unwrap();
+        write_text(&mut out, "31m", format_args!("{}", 2), true, true).unwrap();
+        write_text(&mut out, "31m", format_args!("{}", 3), false, false).unwrap();
+        write_text(&mut out, "31m", format_args!("{}", 4), true, false).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b[31m1\x1b[0m\x1b[31m2\x1b[0m\n34\n"
+        );
+    }
+
+    #[test]
+    fn test_print_codes() {
+        assert_eq!(print_codes("1;31m", ColorDepth::None), "1m");
+        assert_eq!(print_codes("31;48;5;17m", ColorDepth::None), "");
+        assert_eq!(print_codes("31;48;5;17m", ColorDepth::Ansi16), "31;40m");
+        assert_eq!(print_codes("m", ColorDepth::TrueColor), "m");
+        assert_eq!(print_codes("", ColorDepth::None), "");
+    }
+
+    #[test]
+    fn test_render_args() {
+        let gradient = Gradient::new([Color::Rgb(0, 0, 0), Color::Rgb(0, 0, 255)]);
+        let depth = Some(ColorDepth::TrueColor);
+        let render = |out: &mut String, text: &str| gradient.render(out, text, depth);
+
+        assert_eq!(
+            render_args(format_args!("{}{}", 1, 2), true, render),
+            "\x1b[38;2;0;0;0m1\x1b[38;2;0;0;255m2\x1b[0m\n"
+        );
+        assert_eq!(render_args(format_args!("ab"), true, String::push_str), "ab\n");
+        assert_eq!(render_args(format_args!("ab"), false, String::push_str), "ab");
+    }
+
+    // Runs itself in a child process, which must exit with status 0 rather
+    // than return from `handle_print_result`.
+    #[test]
+    fn test_broken_pipe_exits() {
+        const CHILD: &str = "COLOR_MACROS_BROKEN_PIPE_CHILD";
+
+        if env::var_os(CHILD).is_some() {
+            let err = io::Error::from(io::ErrorKind::BrokenPipe);
+            handle_print_result(Err(err), "stdout");
+            panic!("handle_print_result returned after a broken pipe");
+        }
+
+        let status = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "stream::tests::test_broken_pipe_exits", "--nocapture"])
+            .env(CHILD, "1")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    #[should_panic(expected = "failed printing to stderr")]
+    fn test_other_errors_panic() {
+        let err = io::Error::from(io::ErrorKind::PermissionDenied);
+        handle_print_result(Err(err), "stderr");
+    }
+}
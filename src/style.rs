@@ -0,0 +1,351 @@
+//! Styles and style string parsing.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parse;
+use crate::{Attribute, Attributes, Color};
+
+/// The foreground and background colors and the text attributes of a style.
+///
+/// A `Style` can be parsed from a style string or built with its builder
+/// methods, and is accepted by the `x_styled!()` macros in place of a style
+/// string.
+///
+/// A `None` color leaves the current color unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Attribute, Color, Style};
+///
+/// let style: Style = "bold bright red on blue".parse().unwrap();
+///
+/// assert_eq!(style.fg, Some(Color::BrightRed));
+/// assert_eq!(style.bg, Some(Color::Blue));
+/// assert!(style.attrs.contains(Attribute::Bold));
+///
+/// // The same style built programmatically.
+/// let built = Style::new().fg(Color::BrightRed).on(Color::Blue).bold();
+///
+/// assert_eq!(built, style);
+/// assert_eq!(built.to_string(), "\x1b[1;91;44m");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    /// The foreground color.
+    pub fg: Option<Color>,
+    /// The background color.
+    pub bg: Option<Color>,
+    /// The text attributes.
+    pub attrs: Attributes,
+}
+
+impl Style {
+    /// Returns a new `Style` that leaves the current colors unchanged and
+    /// has no text attributes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { fg: None, bg: None, attrs: Attributes::new() }
+    }
+
+    /// Returns this style with its foreground color set to `color`.
+    #[must_use]
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Returns this style with its background color set to `color`.
+    #[must_use]
+    pub const fn on(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Returns this style with the text attribute `attr` added to it.
+    #[must_use]
+    pub const fn attr(mut self, attr: Attribute) -> Self {
+        self.attrs = self.attrs.with(attr);
+        self
+    }
+
+    /// Returns this style with bold text.
+    #[must_use]
+    pub const fn bold(self) -> Self {
+        self.attr(Attribute::Bold)
+    }
+
+    /// Returns this style with dim text.
+    #[must_use]
+    pub const fn dim(self) -> Self {
+        self.attr(Attribute::Dim)
+    }
+
+    /// Returns this style with italic text.
+    #[must_use]
+    pub const fn italic(self) -> Self {
+        self.attr(Attribute::Italic)
+    }
+
+    /// Returns this style with underlined text.
+    #[must_use]
+    pub const fn underline(self) -> Self {
+        self.attr(Attribute::Underline)
+    }
+
+    /// Returns this style with blinking text.
+    #[must_use]
+    pub const fn blink(self) -> Self {
+        self.attr(Attribute::Blink)
+    }
+
+    /// Returns this style with its foreground and background colors reversed.
+    #[must_use]
+    pub const fn reverse(self) -> Self {
+        self.attr(Attribute::Reverse)
+    }
+
+    /// Returns this style with hidden text.
+    #[must_use]
+    pub const fn hidden(self) -> Self {
+        self.attr(Attribute::Hidden)
+    }
+
+    /// Returns this style with strikethrough text.
+    #[must_use]
+    pub const fn strikethrough(self) -> Self {
+        self.attr(Attribute::Strikethrough)
+    }
+
+    /// Returns true if this style leaves the current colors unchanged and
+    /// has no text attributes.
+    #[must_use]
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.attrs.is_empty()
+    }
+}
+
+impl fmt::Display for Style {
+    /// Writes the SGR escape sequence that turns this style on (e.g.
+    /// "\x1b[1;31;44m"). Nothing is written for a plain style.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plain() {
+            return Ok(());
+        }
+        write!(f, "\u{001b}[{}", parse::codes(self).as_str())
+    }
+}
+
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    /// Parses an "X on Y" style string (e.g. "bright green on white") using
+    /// the same grammar as the `x_styled!()` macros.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseStyleError` describing the offending token if `s` is
+    /// not a valid style string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::parse_style(s).map_err(|e| ParseStyleError::new(s, e))
+    }
+}
+
+/// The kinds of errors that can occur while parsing a style string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseStyleErrorKind {
+    /// The style string contains no tokens.
+    Empty,
+    /// A token is not a known color name, attribute name or keyword (e.g.
+    /// "purpel").
+    UnknownToken,
+    /// A color was expected after the token (e.g. "on" in "red on").
+    MissingColor,
+    /// A valid token appeared where it is not allowed (e.g. "blue" in
+    /// "red blue").
+    UnexpectedToken,
+    /// A hex, RGB or 256-color value is malformed or out of range (e.g.
+    /// "#ff88" or "rgb(300, 0, 0)").
+    InvalidColorValue,
+}
+
+impl ParseStyleErrorKind {
+    /// Returns a description of the error that precedes the offending token
+    /// in error messages.
+    pub(crate) const fn description(self) -> &'static str {
+        match self {
+            Self::Empty => "the style string is empty",
+            Self::UnknownToken => "unknown color or attribute name",
+            Self::MissingColor => "expected a color after",
+            Self::UnexpectedToken => "unexpected token",
+            Self::InvalidColorValue => "invalid color value",
+        }
+    }
+}
+
+/// An error returned when parsing an invalid style string or color name.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::Style;
+///
+/// let err = "bright purpel on white".parse::<Style>().unwrap_err();
+///
+/// assert_eq!(err.token(), "purpel");
+/// assert_eq!(err.position(), 7);
+/// assert_eq!(err.suggestion(), Some("magenta"));
+/// assert_eq!(
+///     err.to_string(),
+///     "unknown color or attribute name \"purpel\" at position 7; did you mean \"magenta\"?"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseStyleError {
+    kind: ParseStyleErrorKind,
+    token: String,
+    position: usize,
+    suggestion: Option<&'static str>,
+}
+
+impl ParseStyleError {
+    /// Returns a new `ParseStyleError` for an error in `style`.
+    pub(crate) fn new(style: &str, err: parse::Error) -> Self {
+        Self {
+            kind: err.kind,
+            token: err.token(style).to_string(),
+            position: err.start,
+            suggestion: err.suggestion(style),
+        }
+    }
+
+    /// Returns the kind of error.
+    #[must_use]
+    pub const fn kind(&self) -> ParseStyleErrorKind {
+        self.kind
+    }
+
+    /// Returns the offending token.
+    #[must_use]
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns the byte offset of the offending token in the style string.
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the valid word closest to the offending token, if there is
+    /// one that is close enough to be a plausible correction.
+    #[must_use]
+    pub const fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
+}
+
+impl fmt::Display for ParseStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind == ParseStyleErrorKind::Empty {
+            return f.write_str(self.kind.description());
+        }
+
+        write!(
+            f,
+            "{} \"{}\" at position {}",
+            self.kind.description(),
+            self.token,
+            self.position
+        )?;
+
+        if let Some(suggestion) = self.suggestion {
+            write!(f, "; did you mean \"{suggestion}\"?")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseStyleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseStyleErrorKind, Style};
+    use crate::{Attribute, Attributes, Color};
+
+    #[test]
+    fn test_style_from_str() {
+        let parse = |s: &str| s.parse::<Style>().unwrap();
+        let style = |fg, bg, attrs| Style { fg, bg, attrs };
+
+        assert_eq!(parse("red"), style(Some(Color::Red), None, Attributes::new()));
+        assert_eq!(
+            parse("on Bright White"),
+            style(None, Some(Color::BrightWhite), Attributes::new())
+        );
+        assert_eq!(parse("current on current"), Style::new());
+        assert_eq!(
+            parse("bright green on reset"),
+            style(Some(Color::BrightGreen), Some(Color::Reset), Attributes::new())
+        );
+        assert_eq!(
+            parse("bold underline bright yellow on blue"),
+            style(
+                Some(Color::BrightYellow),
+                Some(Color::Blue),
+                Attribute::Bold | Attribute::Underline
+            )
+        );
+        assert_eq!(
+            parse("italic"),
+            style(None, None, Attributes::from(Attribute::Italic))
+        );
+    }
+
+    #[test]
+    fn test_style_builder_and_display() {
+        let style = Style::new()
+            .fg(Color::Red)
+            .on(Color::Rgb(1, 2, 3))
+            .underline()
+            .bold();
+
+        assert_eq!(style, "bold underline red on rgb(1, 2, 3)".parse().unwrap());
+        assert_eq!(style.to_string(), "\x1b[1;4;31;48;2;1;2;3m");
+        assert_eq!(Style::new().on(Color::Color256(236)).to_string(), "\x1b[48;5;236m");
+        assert_eq!(Style::new().attr(Attribute::Strikethrough).to_string(), "\x1b[9m");
+        assert!(Style::new().is_plain());
+        assert_eq!(Style::new().to_string(), "");
+    }
+
+    #[test]
+    fn test_style_from_str_errors() {
+        let err = |s: &str| s.parse::<Style>().unwrap_err();
+
+        assert_eq!(err("").kind(), ParseStyleErrorKind::Empty);
+        assert_eq!(err("   ").to_string(), "the style string is empty");
+
+        let e = err("red on");
+        assert_eq!(e.kind(), ParseStyleErrorKind::MissingColor);
+        assert_eq!((e.token(), e.position()), ("on", 4));
+
+        let e = err("red on bright");
+        assert_eq!(e.kind(), ParseStyleErrorKind::MissingColor);
+        assert_eq!((e.token(), e.position()), ("bright", 7));
+
+        let e = err("red blue");
+        assert_eq!(e.kind(), ParseStyleErrorKind::UnexpectedToken);
+        assert_eq!(e.to_string(), "unexpected token \"blue\" at position 4");
+
+        let e = err("bold on blue on red");
+        assert_eq!(e.kind(), ParseStyleErrorKind::UnexpectedToken);
+        assert_eq!((e.token(), e.position()), ("on", 13));
+
+        let e = err("red on grey");
+        assert_eq!(e.kind(), ParseStyleErrorKind::UnknownToken);
+        assert_eq!(e.suggestion(), Some("bright black"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Styled values that can be used anywhere a `Display` is accepted.
+
+use std::fmt;
+
+use crate::control;
+use crate::{Attribute, Color, Style};
+
+/// A value displayed with a `Style`.
+///
+/// The escape sequence that turns the style on is written before the value
+/// and a reset sequence is written after it. Formatting flags such as width,
+/// precision and alignment are forwarded to the value, so `{:>8}` pads the
+/// value itself rather than the escape sequences.
+///
+/// No escape sequences are written when the color choice is
+/// `ColorChoice::Never`.
+///
+/// `Styled` values are usually created with the methods of the `Colorize`
+/// trait.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Color, Style, Styled};
+///
+/// let styled = Styled::new("done", Style::new().fg(Color::Green));
+///
+/// assert_eq!(format!("[{styled:>6}]"), "[\x1b[32m  done\x1b[0m]");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Styled<T> {
+    value: T,
+    style: Style,
+}
+
+impl<T> Styled<T> {
+    /// Returns a new `Styled` that displays `value` with `style`.
+    #[must_use]
+    pub const fn new(value: T, style: Style) -> Self {
+        Self { value, style }
+    }
+
+    /// Returns a reference to the styled value.
+    #[must_use]
+    pub const fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the style of the value.
+    #[must_use]
+    pub const fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Returns the styled value, discarding its style.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Writes the value with its style using `fmt_value` to format the value.
+    fn fmt_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        fmt_value: fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        if self.style.is_plain() || !control::colors_enabled(None) {
+            return fmt_value(&self.value, f);
+        }
+
+        write!(f, "{}", self.style)?;
+        fmt_value(&self.value, f)?;
+        f.write_str("\u{001b}[0m")
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, fmt::Display::fmt)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, fmt::Debug::fmt)
+    }
+}
+
+/// Defines the `Colorize` trait and the matching methods of `Styled`, which
+/// update the style of an already styled value instead of wrapping it again.
+macro_rules! colorize_methods {
+    ($(
+        $(#[doc = $doc:expr])*
+        fn $name:ident($($arg:ident: $ty:ty),*) => $method:ident($($val:expr),*);
+    )*) => {
+        /// Extension trait that styles any `Display` value.
+        ///
+        /// Each method returns a `Styled` value, and calling further methods
+        /// on it adds to its style, so styles can be chained.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// use color_macros::Colorize;
+        ///
+        /// let message = format!("{} ({})", "done".green().bold(), 42.on_color256(236));
+        ///
+        /// assert_eq!(message, "\x1b[1;32mdone\x1b[0m (\x1b[48;5;236m42\x1b[0m)");
+        /// ```
+        pub trait Colorize: Sized {
+            $(
+                $(#[doc = $doc])*
+                #[must_use]
+                fn $name(self, $($arg: $ty),*) -> Styled<Self> {
+                    Styled::new(self, Style::new().$method($($val),*))
+                }
+            )*
+        }
+
+        impl<T> Styled<T> {
+            $(
+                $(#[doc = $doc])*
+                #[must_use]
+                pub const fn $name(mut self, $($arg: $ty),*) -> Self {
+                    self.style = self.style.$method($($val),*);
+                    self
+                }
+            )*
+        }
+    };
+}
+
+colorize_methods! {
+    /// Sets the foreground color.
+    fn fg(color: Color) => fg(color);
+    /// Sets the background color.
+    fn on(color: Color) => on(color);
+    /// Adds a text attribute.
+    fn attr(attr: Attribute) => attr(attr);
+
+    /// Sets the foreground color to black.
+    fn black() => fg(Color::Black);
+    /// Sets the foreground color to red.
+    fn red() => fg(Color::Red);
+    /// Sets the foreground color to green.
+    fn green() => fg(Color::Green);
+    /// Sets the foreground color to yellow.
+    fn yellow() => fg(Color::Yellow);
+    /// Sets the foreground color to blue.
+    fn blue() => fg(Color::Blue);
+    /// Sets the foreground color to magenta.
+    fn magenta() => fg(Color::Magenta);
+    /// Sets the foreground color to cyan.
+    fn cyan() => fg(Color::Cyan);
+    /// Sets the foreground color to white.
+    fn white() => fg(Color::White);
+    /// Sets the foreground color to bright black.
+    fn bright_black() => fg(Color::BrightBlack);
+    /// Sets the foreground color to bright red.
+    fn bright_red() => fg(Color::BrightRed);
+    /// Sets the foreground color to bright green.
+    fn bright_green() => fg(Color::BrightGreen);
+    /// Sets the foreground color to bright yellow.
+    fn bright_yellow() => fg(Color::BrightYellow);
+    /// Sets the foreground color to bright blue.
+    fn bright_blue() => fg(Color::BrightBlue);
+    /// Sets the foreground color to bright magenta.
+    fn bright_magenta() => fg(Color::BrightMagenta);
+    /// Sets the foreground color to bright cyan.
+    fn bright_cyan() => fg(Color::BrightCyan);
+    /// Sets the foreground color to bright white.
+    fn bright_white() => fg(Color::BrightWhite);
+    /// Sets the foreground color to a 256-color mode color number.
+    fn color256(num: u8) => fg(Color::Color256(num));
+    /// Sets the foreground color to a 24-bit RGB color.
+    fn rgb(r: u8, g: u8, b: u8) => fg(Color::Rgb(r, g, b));
+
+    /// Sets the background color to black.
+    fn on_black() => on(Color::Black);
+    /// Sets the background color to red.
+    fn on_red() => on(Color::Red);
+    /// Sets the background color to green.
+    fn on_green() => on(Color::Green);
+    /// Sets the background color to yellow.
+    fn on_yellow() => on(Color::Yellow);
+    /// Sets the background color to blue.
+    fn on_blue() => on(Color::Blue);
+    /// Sets the background color to magenta.
+    fn on_magenta() => on(Color::Magenta);
+    /// Sets the background color to cyan.
+    fn on_cyan() => on(Color::Cyan);
+    /// Sets the background color to white.
+    fn on_white() => on(Color::White);
+    /// Sets the background color to bright black.
+    fn on_bright_black() => on(Color::BrightBlack);
+    /// Sets the background color to bright red.
+    fn on_bright_red() => on(Color::BrightRed);
+    /// Sets the background color to bright green.
+    fn on_bright_green() => on(Color::BrightGreen);
+    /// Sets the background color to bright yellow.
+    fn on_bright_yellow() => on(Color::BrightYellow);
+    /// Sets the background color to bright blue.
+    fn on_bright_blue() => on(Color::BrightBlue);
+    /// Sets the background color to bright magenta.
+    fn on_bright_magenta() => on(Color::BrightMagenta);
+    /// Sets the background color to bright cyan.
+    fn on_bright_cyan() => on(Color::BrightCyan);
+    /// Sets the background color to bright white.
+    fn on_bright_white() => on(Color::BrightWhite);
+    /// Sets the background color to a 256-color mode color number.
+    fn on_color256(num: u8) => on(Color::Color256(num));
+    /// Sets the background color to a 24-bit RGB color.
+    fn on_rgb(r: u8, g: u8, b: u8) => on(Color::Rgb(r, g, b));
+
+    /// Adds bold text.
+    fn bold() => bold();
+    /// Adds dim text.
+    fn dim() => dim();
+    /// Adds italic text.
+    fn italic() => italic();
+    /// Adds underlined text.
+    fn underline() => underline();
+    /// Adds blinking text.
+    fn blink() => blink();
+    /// Reverses the foreground and background colors.
+    fn reverse() => reverse();
+    /// Adds hidden text.
+    fn hidden() => hidden();
+    /// Adds strikethrough text.
+    fn strikethrough() => strikethrough();
+}
+
+impl<T: fmt::Display> Colorize for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Colorize, Styled};
+    use crate::{Attribute, Color, Style};
+
+    #[test]
+    fn test_colorize_chaining() {
+        assert_eq!("done".green().bold().to_string(), "\x1b[1;32mdone\x1b[0m");
+        assert_eq!(42.on_color256(236).to_string(), "\x1b[48;5;236m42\x1b[0m");
+        assert_eq!(
+            'x'.rgb(1, 2, 3).on_blue().attr(Attribute::Underline).to_string(),
+            "\x1b[4;38;2;1;2;3;44mx\x1b[0m"
+        );
+
+        let styled = String::from("warn").fg(Color::Yellow).on(Color::Black);
+        assert_eq!(styled.style(), "yellow on black".parse().unwrap());
+        assert_eq!(styled.into_inner(), "warn");
+    }
+
+    #[test]
+    fn test_styled_forwards_format_flags() {
+        assert_eq!(format!("{:>5}", "ab".red()), "\x1b[31m   ab\x1b[0m");
+        assert_eq!(format!("{:<4}|", 7.bold()), "\x1b[1m7   \x1b[0m|");
+        assert_eq!(format!("{:^6.2}", 1.23456.italic()), "\x1b[3m 1.23 \x1b[0m");
+        assert_eq!(format!("{:03}", 5.blue()), "\x1b[34m005\x1b[0m");
+        assert_eq!(format!("{:?}", "a\"b".red()), "\x1b[31m\"a\\\"b\"\x1b[0m");
+    }
+
+    #[test]
+    fn test_plain_styled_has_no_escape_codes() {
+        let styled = Styled::new("plain", Style::new());
+
+        assert_eq!(format!("{styled:>7}"), "  plain");
+        assert_eq!(*styled.value(), "plain");
+    }
+}
@@ -0,0 +1,427 @@
+//! Rendering of styled text as an SVG image of a terminal window.
+
+use std::fmt::Write as _;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::html::{decorations, escape, resolved_colors, FONT_PROPERTIES};
+use crate::layout::grapheme_width;
+use crate::{parse_ansi, Attribute, Palette, Style};
+
+/// The colors of the buttons in the title bar of the window.
+const BUTTON_COLORS: [&str; 3] = ["#ff5f57", "#febc2e", "#28c840"];
+
+/// The distance between the columns of tab stops.
+const TAB_WIDTH: usize = 8;
+
+/// Renders text containing SGR escape sequences (e.g. the output of the write
+/// macros) as an SVG image of a terminal window, using the default `Svg`
+/// options.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{ansi_to_svg, write_styled};
+///
+/// let mut buffer: Vec<u8> = vec![];
+/// write_styled!(&mut buffer, "bold green", "ok");
+/// let text = String::from_utf8(buffer).unwrap();
+///
+/// let svg = ansi_to_svg(&text);
+///
+/// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+/// assert!(svg.contains(">ok</text>"));
+/// ```
+#[must_use]
+pub fn ansi_to_svg(text: &str) -> String {
+    Svg::new().apply(text)
+}
+
+/// Options for rendering styled text as an SVG image of a terminal window.
+///
+/// The text is laid out on a grid of monospaced cells: wide characters take
+/// up two cells, tabs move to the next multiple of 8 columns and each line
+/// of the text is one row. Lines are not wrapped, so the grid is as wide as
+/// the widest line (use `wrap` to wrap them first). The colors of each cell
+/// come from the same model as `Color`, with the basic and bright colors and
+/// the default colors taken from the `Palette`.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{Palette, Svg};
+///
+/// let svg = Svg::new()
+///     .palette(Palette::XTERM.with_background((40, 44, 52)))
+///     .font("Fira Code, monospace", 16.0)
+///     .columns(80)
+///     .title("cargo test");
+///
+/// let image = svg.apply("\x1b[32mtest result: ok\x1b[0m\n");
+///
+/// assert!(image.contains("fill=\"#282c34\""));
+/// assert!(image.contains("font-family=\"Fira Code, monospace\""));
+/// assert!(image.contains(">cargo test</text>"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Svg<'a> {
+    palette: Palette,
+    font_family: &'a str,
+    font_size: f64,
+    columns: usize,
+    title: &'a str,
+}
+
+impl<'a> Svg<'a> {
+    /// Returns the default options, which use the colors of `Palette::XTERM`
+    /// and the platform's monospaced font at a size of 14 pixels.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            palette: Palette::XTERM,
+            font_family: "ui-monospace, SFMono-Regular, Menlo, Consolas, monospace",
+            font_size: 14.0,
+            columns: 0,
+            title: "",
+        }
+    }
+
+    /// Returns these options with the basic and bright colors and the default
+    /// colors taken from `palette`.
+    #[must_use]
+    pub const fn palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Returns these options with the text drawn in the CSS font family
+    /// `family` at a size of `size` pixels. The cells of the grid are 0.6
+    /// times as wide and 1.2 times as high as the font size, which suits
+    /// most monospaced fonts.
+    #[must_use]
+    pub const fn font(mut self, family: &'a str, size: f64) -> Self {
+        self.font_family = family;
+        self.font_size = size;
+        self
+    }
+
+    /// Returns these options with a grid that is at least `columns` columns
+    /// wide.
+    #[must_use]
+    pub const fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Returns these options with `title` shown in the title bar.
+    #[must_use]
+    pub const fn title(mut self, title: &'a str) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Returns `text` rendered as an SVG image.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        let rows = layout(text);
+        let columns = rows
+            .iter()
+            .filter_map(|row| row.last().map(|cell| cell.column + cell.width))
+            .fold(self.columns, usize::max);
+
+        let (cell_width, line_height) = (self.cell_width(), self.line_height());
+
+        #[allow(clippy::cast_precision_loss)]
+        let (width, height) = (
+            (columns as f64).mul_add(cell_width, 2.0 * self.padding()),
+            (rows.len() as f64).mul_add(line_height, self.top() + self.padding()),
+        );
+
+        let mut out = String::new();
+        let (width, height) = (num(width), num(height));
+
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">"
+        );
+        let _ = writeln!(
+            out,
+            "<rect width=\"{width}\" height=\"{height}\" rx=\"{}\" fill=\"{}\"/>",
+            num(self.font_size / 2.0),
+            hex(self.palette.background())
+        );
+        self.title_bar(&mut out);
+
+        let _ = write!(
+            out,
+            "<g font-family=\"{}\" font-size=\"{}\" xml:space=\"preserve\">",
+            attribute(self.font_family),
+            num(self.font_size)
+        );
+        out.push('\n');
+
+        for (row, cells) in (0_u32..).zip(&rows) {
+            for cell in cells {
+                self.cell(&mut out, cell, f64::from(row).mul_add(line_height, self.top()));
+            }
+        }
+
+        out.push_str("</g>\n</svg>\n");
+        out
+    }
+
+    /// Returns the width of a cell of the grid.
+    fn cell_width(&self) -> f64 {
+        self.font_size * 0.6
+    }
+
+    /// Returns the height of a row of the grid.
+    fn line_height(&self) -> f64 {
+        self.font_size * 1.2
+    }
+
+    /// Returns the distance between the grid and the edges of the window.
+    const fn padding(&self) -> f64 {
+        self.font_size
+    }
+
+    /// Returns the height of the title bar.
+    fn bar_height(&self) -> f64 {
+        self.font_size * 2.0
+    }
+
+    /// Returns the position of the top of the grid.
+    fn top(&self) -> f64 {
+        self.bar_height() + self.padding()
+    }
+
+    /// Appends the title bar with its buttons and the title to `out`.
+    fn title_bar(&self, out: &mut String) {
+        let radius = self.font_size * 0.4;
+        let bar_height = self.bar_height();
+
+        for (idx, color) in (0_u32..).zip(BUTTON_COLORS) {
+            let x = f64::from(idx).mul_add(radius * 3.0, self.font_size + radius);
+            let _ = writeln!(
+                out,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{color}\"/>",
+                num(x),
+                num(bar_height / 2.0),
+                num(radius)
+            );
+        }
+
+        if !self.title.is_empty() {
+            let mut title = String::new();
+            escape(&mut title, self.title);
+            let _ = writeln!(
+                out,
+                "<text x=\"50%\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\" \
+                 font-family=\"{}\" font-size=\"{}\" fill=\"{}\" opacity=\"0.7\">{title}</text>",
+                num(bar_height / 2.0),
+                attribute(self.font_family),
+                num(self.font_size),
+                hex(self.palette.foreground())
+            );
+        }
+    }
+
+    /// Appends the background and the text of a run of cells with the same
+    /// style to `out`, on the row whose top is at `top`.
+    fn cell(&self, out: &mut String, cell: &Cell, top: f64) {
+        let style = cell.style;
+        #[allow(clippy::cast_precision_loss)]
+        let (x, width) = (
+            (cell.column as f64).mul_add(self.cell_width(), self.padding()),
+            cell.width as f64 * self.cell_width(),
+        );
+        let (fg, bg) = resolved_colors(style, &self.palette);
+
+        if let Some(rgb) = bg.and_then(|color| self.palette.rgb(color)) {
+            let _ = writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                num(x),
+                num(top),
+                num(width),
+                num(self.line_height()),
+                hex(rgb)
+            );
+        }
+
+        if style.attrs.contains(Attribute::Hidden) || cell.text.trim().is_empty() {
+            return;
+        }
+
+        let fg = fg.and_then(|color| self.palette.rgb(color)).unwrap_or_else(|| self.palette.foreground());
+        let _ = write!(
+            out,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\"",
+            num(x),
+            num(top + self.font_size),
+            hex(fg)
+        );
+
+        for (attr, name, value) in FONT_PROPERTIES {
+            if style.attrs.contains(attr) {
+                let _ = write!(out, " {name}=\"{value}\"");
+            }
+        }
+
+        let decorations = decorations(style);
+        if !decorations.is_empty() {
+            let _ = write!(out, " text-decoration=\"{}\"", decorations.join(" "));
+        }
+
+        out.push('>');
+        escape(out, &cell.text);
+        out.push_str("</text>\n");
+    }
+}
+
+impl Default for Svg<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A run of cells with the same style on a row of the grid.
+struct Cell {
+    style: Style,
+    /// The column of the first cell.
+    column: usize,
+    /// The number of cells.
+    width: usize,
+    text: String,
+}
+
+/// Lays out `text` on the grid, returning the runs of cells of each row.
+fn layout(text: &str) -> Vec<Vec<Cell>> {
+    let mut rows: Vec<Vec<Cell>> = Vec::new();
+    let mut row: Vec<Cell> = Vec::new();
+    let mut column = 0;
+
+    for (style, span) in parse_ansi(text) {
+        for (idx, line) in span.split('\n').enumerate() {
+            if idx > 0 {
+                rows.push(std::mem::take(&mut row));
+                column = 0;
+            }
+
+            for grapheme in line.graphemes(true) {
+                let (text, width) = if grapheme == "\t" {
+                    let width = TAB_WIDTH - column % TAB_WIDTH;
+                    (" ".repeat(width), width)
+                } else {
+                    (grapheme.to_string(), grapheme_width(grapheme))
+                };
+
+                // Control characters other than tabs take up no cells.
+                if width == 0 {
+                    continue;
+                }
+
+                match row.last_mut() {
+                    Some(cell) if cell.style == style && cell.column + cell.width == column => {
+                        cell.text.push_str(&text);
+                        cell.width += width;
+                    }
+                    _ => row.push(Cell { style, column, width, text }),
+                }
+                column += width;
+            }
+        }
+    }
+
+    // A trailing newline does not start another row.
+    if rows.is_empty() || !row.is_empty() || !text.ends_with('\n') {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Returns `value` escaped for an attribute value.
+fn attribute(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    escape(&mut out, value);
+    out
+}
+
+/// Returns an RGB value as a CSS hex color (e.g. "#ff8800").
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Returns `value` with at most two decimals and without trailing zeros.
+fn num(value: f64) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layout, num, Svg};
+    use crate::{Color, Palette, Style};
+
+    #[test]
+    fn test_layout() {
+        let rows = layout("a\x1b[31mb日\x1b[0m\tc\n\n\x1b[44mx\r\n");
+        let cells: Vec<Vec<(Style, usize, usize, &str)>> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| (cell.style, cell.column, cell.width, cell.text.as_str()))
+                    .collect()
+            })
+            .collect();
+        let red = Style::new().fg(Color::Red);
+        let blue = Style::new().on(Color::Blue);
+
+        assert_eq!(
+            cells,
+            [
+                vec![
+                    (Style::new(), 0, 1, "a"),
+                    (red, 1, 3, "b日"),
+                    (Style::new(), 4, 5, "    c"),
+                ],
+                vec![],
+                vec![(blue, 0, 1, "x")],
+            ]
+        );
+        assert_eq!(layout("").len(), 1);
+    }
+
+    #[test]
+    fn test_svg() {
+        let svg = Svg::new()
+            .palette(Palette::XTERM.with(Color::Red, (255, 0, 0)))
+            .font("Mono \"X\"", 10.0)
+            .title("<demo>")
+            .apply("\x1b[1;4;31;47mab\x1b[0m \x1b[7mc\x1b[0m\x1b[8mhidden\x1b[0m");
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"80\" height=\"52\" \
+             viewBox=\"0 0 80 52\">\n"
+        ));
+        assert!(svg.contains("font-family=\"Mono &quot;X&quot;\""));
+        assert!(svg.contains(">&lt;demo&gt;</text>"));
+        assert!(svg.contains("<rect x=\"10\" y=\"30\" width=\"12\" height=\"12\" fill=\"#e5e5e5\"/>"));
+        assert!(svg.contains(
+            "<text x=\"10\" y=\"40\" fill=\"#ff0000\" font-weight=\"bold\" \
+             text-decoration=\"underline\">ab</text>"
+        ));
+        assert!(svg.contains("<rect x=\"28\" y=\"30\" width=\"6\" height=\"12\" fill=\"#e5e5e5\"/>"));
+        assert!(svg.contains("<text x=\"28\" y=\"40\" fill=\"#000000\">c</text>"));
+        assert!(!svg.contains("hidden"));
+        assert!(svg.ends_with("</g>\n</svg>\n"));
+    }
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(14.0), "14");
+        assert_eq!(num(8.4), "8.4");
+        assert_eq!(num(1.0 / 3.0), "0.33");
+    }
+}
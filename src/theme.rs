@@ -0,0 +1,212 @@
+//! Named styles that style strings can refer to by name.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+use crate::{Color, Style};
+
+/// A registry of named styles (e.g. "error" or "warning").
+///
+/// A style string that is a single word is looked up in the current theme
+/// (see `set_theme`) before it is parsed as a color or attribute name, so
+/// `eprintln_styled!("error", ...)` uses whatever style the theme gives
+/// "error". Names are matched ignoring ASCII case, and the text of a name
+/// that is in neither the theme nor the built-in names is written without
+/// escape codes.
+///
+/// `Theme::default()` returns the built-in theme:
+///
+/// | Name      | Style              |
+/// |-----------|--------------------|
+/// | `error`   | bold red           |
+/// | `warning` | bold yellow        |
+/// | `info`    | cyan               |
+/// | `success` | bold green         |
+/// | `debug`   | bright black       |
+/// | `hint`    | italic bright blue |
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{set_theme, theme, write_styled};
+///
+/// let mut buffer: Vec<u8> = vec![];
+///
+/// write_styled!(&mut buffer, "error", "failed");
+/// assert_eq!(buffer.as_slice(), b"\x1b[1;31mfailed\x1b[0m");
+///
+/// // Add a name to the current theme.
+/// let mut custom = theme();
+/// custom.insert("path", "underline cyan".parse().unwrap());
+/// set_theme(custom);
+///
+/// buffer.clear();
+/// write_styled!(&mut buffer, "path", "/tmp");
+/// assert_eq!(buffer.as_slice(), b"\x1b[4;36m/tmp\x1b[0m");
+///
+/// // Any word can be a name, even one that is close to a built-in name.
+/// set_theme(theme().with("ok", "green".parse().unwrap()));
+///
+/// buffer.clear();
+/// write_styled!(&mut buffer, "ok", "done");
+/// assert_eq!(buffer.as_slice(), b"\x1b[32mdone\x1b[0m");
+///
+/// // A theme can also redefine a built-in name.
+/// set_theme(theme().with("red", "bright red".parse().unwrap()));
+///
+/// buffer.clear();
+/// write_styled!(&mut buffer, "red", "x");
+/// write_styled!(&mut buffer, String::from("red"), "y");
+/// assert_eq!(buffer.as_slice(), b"\x1b[91mx\x1b[0m\x1b[91my\x1b[0m");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    styles: BTreeMap<String, Style>,
+}
+
+impl Theme {
+    /// Returns a new, empty theme.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { styles: BTreeMap::new() }
+    }
+
+    /// Returns this theme with `name` mapped to `style`.
+    #[must_use]
+    pub fn with(mut self, name: &str, style: Style) -> Self {
+        self.insert(name, style);
+        self
+    }
+
+    /// Maps `name` to `style`, returning the style `name` was previously
+    /// mapped to, if any.
+    ///
+    /// Only names without whitespace can be used in style strings.
+    pub fn insert(&mut self, name: &str, style: Style) -> Option<Style> {
+        self.styles.insert(name.to_ascii_lowercase(), style)
+    }
+
+    /// Removes `name` from this theme, returning the style it was mapped to,
+    /// if any.
+    pub fn remove(&mut self, name: &str) -> Option<Style> {
+        self.styles.remove(key(name).as_ref())
+    }
+
+    /// Returns the style `name` is mapped to, ignoring ASCII case.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<Style> {
+        self.styles.get(key(name).as_ref()).copied()
+    }
+
+    /// Returns an iterator over the names and styles of this theme, sorted
+    /// by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Style)> {
+        self.styles.iter().map(|(name, style)| (name.as_str(), *style))
+    }
+}
+
+impl<'a> Extend<(&'a str, Style)> for Theme {
+    /// Adds the names and styles of `iter` to this theme, replacing the
+    /// styles of names that are already in it.
+    fn extend<I: IntoIterator<Item = (&'a str, Style)>>(&mut self, iter: I) {
+        for (name, style) in iter {
+            self.insert(name, style);
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+            .with("error", Style::new().fg(Color::Red).bold())
+            .with("warning", Style::new().fg(Color::Yellow).bold())
+            .with("info", Style::new().fg(Color::Cyan))
+            .with("success", Style::new().fg(Color::Green).bold())
+            .with("debug", Style::new().fg(Color::BrightBlack))
+            .with("hint", Style::new().fg(Color::BrightBlue).italic())
+    }
+}
+
+/// Returns `name` in the lowercase form the names of a theme are stored in.
+fn key(name: &str) -> Cow<'_, str> {
+    if name.bytes().any(|byte| byte.is_ascii_uppercase()) {
+        Cow::Owned(name.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// The process-wide theme.
+static THEME: LazyLock<RwLock<Theme>> = LazyLock::new(|| RwLock::new(Theme::default()));
+
+/// Replaces the process-wide theme that style strings are looked up in.
+///
+/// # Example
+///
+/// ```rust
+/// use color_macros::{set_theme, theme, Color, Style, Theme};
+///
+/// set_theme(Theme::default().with("error", Style::new().fg(Color::BrightRed)));
+///
+/// assert_eq!(theme().get("ERROR"), Some(Style::new().fg(Color::BrightRed)));
+/// ```
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap_or_else(PoisonError::into_inner) = theme;
+}
+
+/// Returns a copy of the process-wide theme.
+#[must_use]
+pub fn theme() -> Theme {
+    THEME.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Returns the style that the style string `style` names in the process-wide
+/// theme, or `None` if it is not a single word in the theme.
+pub fn lookup(style: &str) -> Option<Style> {
+    let name = style.trim_ascii();
+
+    if name.is_empty() || name.bytes().any(|byte| byte.is_ascii_whitespace()) {
+        return None;
+    }
+    THEME.read().unwrap_or_else(PoisonError::into_inner).get(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup, set_theme, theme, Theme};
+    use crate::{Color, Style};
+
+    #[test]
+    fn test_theme_names_ignore_case() {
+        let mut theme = Theme::new().with("Note", Style::new().bold());
+
+        assert_eq!(theme.get("note"), Some(Style::new().bold()));
+        assert_eq!(theme.get("NOTE"), Some(Style::new().bold()));
+        assert_eq!(theme.insert("nOtE", Style::new().dim()), Some(Style::new().bold()));
+        assert_eq!(theme.iter().collect::<Vec<_>>(), [("note", Style::new().dim())]);
+        assert_eq!(theme.remove("NOTE"), Some(Style::new().dim()));
+        assert_eq!(theme.get("note"), None);
+    }
+
+    #[test]
+    fn test_default_theme() {
+        let theme = Theme::default();
+        let names: Vec<&str> = theme.iter().map(|(name, _)| name).collect();
+
+        assert_eq!(names, ["debug", "error", "hint", "info", "success", "warning"]);
+        assert_eq!(theme.get("error"), Some(Style::new().fg(Color::Red).bold()));
+    }
+
+    #[test]
+    fn test_lookup() {
+        // Only add names, since other tests use the process-wide theme.
+        set_theme(theme().with("theme-test", Style::new().fg(Color::Blue)));
+
+        assert_eq!(lookup(" theme-test "), Some(Style::new().fg(Color::Blue)));
+        assert_eq!(lookup("warning"), Some(Style::new().fg(Color::Yellow).bold()));
+        assert_eq!(lookup("theme-test on red"), None);
+        assert_eq!(lookup("red"), None);
+        assert_eq!(lookup(""), None);
+    }
+}
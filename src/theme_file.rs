@@ -0,0 +1,711 @@
+//! Loading themes from TOML and JSON files.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{ParseStyleError, Style, Theme};
+
+/// The file formats a theme can be loaded from.
+#[derive(Clone, Copy)]
+enum Format {
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl Format {
+    /// Returns the format of the file at `path`, judging by its extension.
+    fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+
+        #[cfg(feature = "toml")]
+        if extension.eq_ignore_ascii_case("toml") {
+            return Some(Self::Toml);
+        }
+
+        #[cfg(feature = "json")]
+        if extension.eq_ignore_ascii_case("json") {
+            return Some(Self::Json);
+        }
+
+        None
+    }
+
+    /// Parses `source` into its top-level entries, where a value that is
+    /// not a string is `None`.
+    fn entries(self, source: &str) -> Result<Vec<(String, Option<String>)>, String> {
+        match self {
+            #[cfg(feature = "toml")]
+            Self::Toml => {
+                let table: toml::Table =
+                    source.parse().map_err(|e: toml::de::Error| e.to_string())?;
+
+                Ok(table
+                    .into_iter()
+                    .map(|(key, value)| match value {
+                        toml::Value::String(style) => (key, Some(style)),
+                        _ => (key, None),
+                    })
+                    .collect())
+            }
+            #[cfg(feature = "json")]
+            Self::Json => json::entries(source),
+        }
+    }
+
+    /// Parses a theme file in this format, reporting every invalid entry.
+    fn parse(self, source: &str, path: Option<&Path>) -> Result<Theme, LoadThemeError> {
+        let error = |kind| LoadThemeError { path: path.map(Path::to_path_buf), kind };
+
+        let entries = self.entries(source).map_err(|e| error(LoadThemeErrorKind::Syntax(e)))?;
+
+        let mut theme = Theme::new();
+        let mut invalid = Vec::new();
+
+        for (key, style) in entries {
+            match parse_entry(&key, style.as_deref()) {
+                Ok(style) => {
+                    theme.insert(&key, style);
+                }
+                Err(kind) => invalid.push(ThemeEntryError { key, kind }),
+            }
+        }
+
+        if invalid.is_empty() {
+            Ok(theme)
+        } else {
+            Err(error(LoadThemeErrorKind::InvalidEntries(invalid)))
+        }
+    }
+}
+
+/// A small JSON parser that reads the top-level object of a theme file.
+///
+/// JSON is parsed here rather than with `serde_json` because `serde_json`
+/// implements `PartialEq<Value>` for the primitive types, which breaks type
+/// inference (e.g. of `Vec::new()` compared with a byte slice) in every crate
+/// that depends on this one with the `json` feature enabled.
+#[cfg(feature = "json")]
+mod json {
+    /// The deepest nesting of arrays and objects that is accepted.
+    const MAX_DEPTH: usize = 128;
+
+    /// Parses `source` as a JSON object into its entries, in source order,
+    /// where a value that is not a string is `None`.
+    pub(super) fn entries(source: &str) -> Result<Vec<(String, Option<String>)>, String> {
+        let mut parser = Parser { source, pos: 0 };
+
+        parser.skip_whitespace();
+        if parser.peek() != Some(b'{') {
+            return Err(parser.error("expected an object"));
+        }
+
+        let entries = parser.object(0)?;
+
+        parser.skip_whitespace();
+        if parser.pos < source.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(entries)
+    }
+
+    struct Parser<'a> {
+        source: &'a str,
+        pos: usize,
+    }
+
+    impl Parser<'_> {
+        fn peek(&self) -> Option<u8> {
+            self.source.as_bytes().get(self.pos).copied()
+        }
+
+        fn skip_whitespace(&mut self) {
+            while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+                self.pos += 1;
+            }
+        }
+
+        /// Consumes `byte` after any whitespace, or fails with `expected`.
+        fn expect(&mut self, byte: u8, expected: &str) -> Result<(), String> {
+            self.skip_whitespace();
+            if self.peek() == Some(byte) {
+                self.pos += 1;
+                Ok(())
+            } else {
+                Err(self.error(expected))
+            }
+        }
+
+        /// Returns `message` with the line and column of the current position.
+        fn error(&self, message: &str) -> String {
+            let before = &self.source[..self.pos];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+
+            if self.pos < self.source.len() {
+                format!("{message} at line {line} column {column}")
+            } else {
+                format!("{message}, found end of input at line {line} column {column}")
+            }
+        }
+
+        /// Parses the object that starts at the current `{`.
+        fn object(&mut self, depth: usize) -> Result<Vec<(String, Option<String>)>, String> {
+            self.pos += 1;
+            let mut entries = Vec::new();
+
+            self.skip_whitespace();
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                return Ok(entries);
+            }
+
+            loop {
+                self.skip_whitespace();
+                if self.peek() != Some(b'"') {
+                    return Err(self.error("expected a string key"));
+                }
+                let key = self.string()?;
+
+                self.expect(b':', "expected ':'")?;
+                let value = self.value(depth + 1)?;
+                entries.push((key, value));
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b'}') => {
+                        self.pos += 1;
+                        return Ok(entries);
+                    }
+                    _ => return Err(self.error("expected ',' or '}'")),
+                }
+            }
+        }
+
+        /// Parses the array that starts at the current `[`, discarding it.
+        fn array(&mut self, depth: usize) -> Result<(), String> {
+            self.pos += 1;
+
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(());
+            }
+
+            loop {
+                self.value(depth + 1)?;
+
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b']') => {
+                        self.pos += 1;
+                        return Ok(());
+                    }
+                    _ => return Err(self.error("expected ',' or ']'")),
+                }
+            }
+        }
+
+        /// Parses any value, returning it only if it is a string.
+        fn value(&mut self, depth: usize) -> Result<Option<String>, String> {
+            if depth > MAX_DEPTH {
+                return Err(self.error("nested too deeply"));
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'"') => return self.string().map(Some),
+                Some(b'{') => {
+                    self.object(depth)?;
+                }
+                Some(b'[') => self.array(depth)?,
+                Some(b'-' | b'0'..=b'9') => self.number()?,
+                _ => {
+                    let rest = &self.source[self.pos..];
+                    let literal = ["true", "false", "null"]
+                        .into_iter()
+                        .find(|literal| rest.starts_with(literal))
+                        .ok_or_else(|| self.error("expected a value"))?;
+                    self.pos += literal.len();
+                }
+            }
+
+            Ok(None)
+        }
+
+        /// Parses the number at the current position, discarding it.
+        fn number(&mut self) -> Result<(), String> {
+            if self.peek() == Some(b'-') {
+                self.pos += 1;
+            }
+
+            match self.peek() {
+                Some(b'0') => self.pos += 1,
+                Some(b'1'..=b'9') => self.digits(),
+                _ => return Err(self.error("expected a digit")),
+            }
+
+            if self.peek() == Some(b'.') {
+                self.pos += 1;
+                if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                    return Err(self.error("expected a digit"));
+                }
+                self.digits();
+            }
+
+            if matches!(self.peek(), Some(b'e' | b'E')) {
+                self.pos += 1;
+                if matches!(self.peek(), Some(b'+' | b'-')) {
+                    self.pos += 1;
+                }
+                if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                    return Err(self.error("expected a digit"));
+                }
+                self.digits();
+            }
+
+            Ok(())
+        }
+
+        fn digits(&mut self) {
+            while matches!(self.peek(), Some(b'0'..=b'9')) {
+                self.pos += 1;
+            }
+        }
+
+        /// Parses the string that starts at the current `"`.
+        fn string(&mut self) -> Result<String, String> {
+            self.pos += 1;
+            let mut string = String::new();
+
+            loop {
+                let rest = &self.source[self.pos..];
+                let end = rest
+                    .find(|c: char| c == '"' || c == '\\' || c < ' ')
+                    .ok_or_else(|| {
+                        self.pos = self.source.len();
+                        self.error("unterminated string")
+                    })?;
+                string.push_str(&rest[..end]);
+                self.pos += end;
+
+                match self.peek() {
+                    Some(b'"') => {
+                        self.pos += 1;
+                        return Ok(string);
+                    }
+                    Some(b'\\') => {
+                        self.pos += 1;
+                        string.push(self.escape()?);
+                    }
+                    _ => return Err(self.error("control character in string")),
+                }
+            }
+        }
+
+        /// Parses the escape sequence after a `\`.
+        fn escape(&mut self) -> Result<char, String> {
+            let c = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    self.pos += 1;
+                    let high = self.hex4()?;
+
+                    let code = if (0xd800..0xdc00).contains(&high) {
+                        if !self.source[self.pos..].starts_with("\\u") {
+                            return Err(self.error("expected a low surrogate"));
+                        }
+                        self.pos += 2;
+                        let low = self.hex4()?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return Err(self.error("expected a low surrogate"));
+                        }
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    } else {
+                        high
+                    };
+
+                    return char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"));
+                }
+                _ => return Err(self.error("invalid escape")),
+            };
+
+            self.pos += 1;
+            Ok(c)
+        }
+
+        /// Parses the four hex digits of a `\u` escape.
+        fn hex4(&mut self) -> Result<u32, String> {
+            let digits = self
+                .source
+                .get(self.pos..self.pos + 4)
+                .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+                .ok_or_else(|| self.error("expected four hex digits"))?;
+            self.pos += 4;
+
+            u32::from_str_radix(digits, 16).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Parses the style string `style` of the theme entry `key`.
+fn parse_entry(key: &str, style: Option<&str>) -> Result<Style, ThemeEntryErrorKind> {
+    if key.is_empty() || key.chars().any(char::is_whitespace) {
+        return Err(ThemeEntryErrorKind::InvalidName);
+    }
+
+    let style = style.ok_or(ThemeEntryErrorKind::NotAString)?;
+    style.parse().map_err(ThemeEntryErrorKind::InvalidStyle)
+}
+
+impl Theme {
+    /// Loads a theme from a TOML (`.toml`) or JSON (`.json`) file that maps
+    /// style names to style strings, e.g. `error = "bold bright red"`.
+    ///
+    /// Requires the `toml` or `json` feature for the respective format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, has an unsupported
+    /// extension or is not a valid file of its format, or if any entry is
+    /// not a valid style string. Every invalid entry is reported with its
+    /// key and offending token.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use color_macros::{set_theme, Theme};
+    ///
+    /// let mut theme = Theme::default();
+    /// theme.extend(Theme::load("theme.toml")?.iter());
+    /// set_theme(theme);
+    /// # Ok::<(), color_macros::LoadThemeError>(())
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadThemeError> {
+        let path = path.as_ref();
+        let error = |kind| LoadThemeError { path: Some(path.to_path_buf()), kind };
+
+        let format =
+            Format::of(path).ok_or_else(|| error(LoadThemeErrorKind::UnsupportedFormat))?;
+        let source = fs::read_to_string(path).map_err(|e| error(LoadThemeErrorKind::Io(e)))?;
+
+        format.parse(&source, Some(path))
+    }
+
+    /// Parses a theme from TOML source that maps style names to style
+    /// strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` is not valid TOML or if any entry is not
+    /// a valid style string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::{Color, Style, Theme};
+    ///
+    /// let theme = Theme::from_toml("error = \"bold bright red\"").unwrap();
+    /// assert_eq!(theme.get("error"), Some(Style::new().fg(Color::BrightRed).bold()));
+    ///
+    /// let err = Theme::from_toml("error = \"bold purpel\"").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid theme entry \"error\": unknown color or attribute name \"purpel\" \
+    ///      at position 5; did you mean \"magenta\"?"
+    /// );
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(source: &str) -> Result<Self, LoadThemeError> {
+        Format::Toml.parse(source, None)
+    }
+
+    /// Parses a theme from a JSON object that maps style names to style
+    /// strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` is not a valid JSON object or if any
+    /// entry is not a valid style string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_macros::{Color, Style, Theme};
+    ///
+    /// let theme = Theme::from_json(r#"{"info": "on blue"}"#).unwrap();
+    /// assert_eq!(theme.get("info"), Some(Style::new().on(Color::Blue)));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(source: &str) -> Result<Self, LoadThemeError> {
+        Format::Json.parse(source, None)
+    }
+}
+
+/// An error returned when loading a theme file fails.
+///
+/// When the file has invalid entries, the error lists each of them.
+#[derive(Debug)]
+pub struct LoadThemeError {
+    path: Option<PathBuf>,
+    kind: LoadThemeErrorKind,
+}
+
+impl LoadThemeError {
+    /// Returns the path of the theme file, or `None` if the theme was not
+    /// loaded from a file.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the kind of error.
+    #[must_use]
+    pub const fn kind(&self) -> &LoadThemeErrorKind {
+        &self.kind
+    }
+
+    /// Returns the invalid entries of the theme file, which is empty unless
+    /// the kind of error is `InvalidEntries`.
+    #[must_use]
+    pub fn entries(&self) -> &[ThemeEntryError] {
+        match &self.kind {
+            LoadThemeErrorKind::InvalidEntries(entries) => entries,
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for LoadThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.as_deref().map(Path::display);
+
+        if let LoadThemeErrorKind::InvalidEntries(entries) = &self.kind {
+            for (idx, entry) in entries.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str("\n")?;
+                }
+                if let Some(path) = &path {
+                    write!(f, "{path}: ")?;
+                }
+                write!(f, "{entry}")?;
+            }
+            return Ok(());
+        }
+
+        if let Some(path) = &path {
+            write!(f, "{path}: ")?;
+        }
+
+        match &self.kind {
+            LoadThemeErrorKind::Io(e) => write!(f, "failed to read the theme file: {e}"),
+            LoadThemeErrorKind::UnsupportedFormat => {
+                f.write_str("unsupported theme file format (expected .toml or .json)")
+            }
+            LoadThemeErrorKind::Syntax(e) => write!(f, "invalid theme file: {e}"),
+            LoadThemeErrorKind::InvalidEntries(_) => Ok(()),
+        }
+    }
+}
+
+impl Error for LoadThemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            LoadThemeErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The kinds of errors that can occur while loading a theme file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadThemeErrorKind {
+    /// The theme file could not be read.
+    Io(io::Error),
+    /// The extension of the theme file is not ".toml" or ".json", or the
+    /// feature for its format is disabled.
+    UnsupportedFormat,
+    /// The theme file is not a valid file of its format, or its top level is
+    /// not a table (an object in JSON).
+    Syntax(String),
+    /// One or more entries of the theme file are invalid.
+    InvalidEntries(Vec<ThemeEntryError>),
+}
+
+/// An invalid entry of a theme file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThemeEntryError {
+    key: String,
+    kind: ThemeEntryErrorKind,
+}
+
+impl ThemeEntryError {
+    /// Returns the key (i.e. the style name) of the entry.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the kind of error.
+    #[must_use]
+    pub const fn kind(&self) -> &ThemeEntryErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ThemeEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid theme entry \"{}\": ", self.key)?;
+
+        match &self.kind {
+            ThemeEntryErrorKind::InvalidName => f.write_str("names must be a single word"),
+            ThemeEntryErrorKind::NotAString => f.write_str("expected a style string"),
+            ThemeEntryErrorKind::InvalidStyle(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for ThemeEntryError {}
+
+/// The kinds of errors that an entry of a theme file can have.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ThemeEntryErrorKind {
+    /// The key is empty or contains whitespace, so no style string can refer
+    /// to it.
+    InvalidName,
+    /// The value is not a string (e.g. a number or a table).
+    NotAString,
+    /// The value is not a valid style string.
+    InvalidStyle(ParseStyleError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoadThemeErrorKind;
+    use crate::{Color, Style, Theme};
+
+    /// Returns a theme with the error style used by the test theme files.
+    fn expected() -> Theme {
+        Theme::new()
+            .with("error", Style::new().fg(Color::BrightRed).bold())
+            .with("path", Style::new().fg(Color::Rgb(255, 136, 0)).underline())
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_from_toml() {
+        use super::ThemeEntryErrorKind;
+
+        let source = "error = \"bold bright red\"\nPath = \"underline #ff8800\"\n";
+        assert_eq!(Theme::from_toml(source).unwrap(), expected());
+
+        let source = "error = [1]\n\"two words\" = \"red\"\nok = \"red on\"";
+        let err = Theme::from_toml(source).unwrap_err();
+        let kinds: Vec<(&str, &ThemeEntryErrorKind)> =
+            err.entries().iter().map(|entry| (entry.key(), entry.kind())).collect();
+
+        assert_eq!(kinds.len(), 3);
+        assert_eq!(kinds[0], ("error", &ThemeEntryErrorKind::NotAString));
+        assert!(matches!(
+            kinds[1],
+            ("ok", ThemeEntryErrorKind::InvalidStyle(e)) if e.token() == "on"
+        ));
+        assert_eq!(kinds[2], ("two words", &ThemeEntryErrorKind::InvalidName));
+
+        let err = Theme::from_toml("error = ").unwrap_err();
+        assert!(matches!(err.kind(), LoadThemeErrorKind::Syntax(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_from_json() {
+        let source = r#"{"error": "bold bright red", "path": "underline #ff8800"}"#;
+        assert_eq!(Theme::from_json(source).unwrap(), expected());
+
+        let err = Theme::from_json("[\"red\"]").unwrap_err();
+        assert!(matches!(err.kind(), LoadThemeErrorKind::Syntax(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_json_entries() {
+        use super::json::entries;
+
+        let source = r#" {"a": "x\"\u00e9\ud83d\ude00", "b": [1, -2.5e3, {"c": null}], "d": true} "#;
+        assert_eq!(
+            entries(source).unwrap(),
+            [
+                (String::from("a"), Some(String::from("x\"\u{e9}\u{1f600}"))),
+                (String::from("b"), None),
+                (String::from("d"), None),
+            ]
+        );
+        assert_eq!(entries("{}").unwrap(), []);
+
+        assert_eq!(entries("{\"a\": 01}").unwrap_err(), "expected ',' or '}' at line 1 column 8");
+        assert_eq!(
+            entries("{\n  \"a\": \"red\"").unwrap_err(),
+            "expected ',' or '}', found end of input at line 2 column 13"
+        );
+        assert_eq!(entries("{} x").unwrap_err(), "trailing characters at line 1 column 4");
+        assert!(entries("{\"a\": \"\\x\"}").is_err());
+        assert!(entries(&"[".repeat(200)).is_err());
+        assert!(entries(&format!("{{\"a\": {}", "[".repeat(200))).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_errors_cite_the_file() {
+        use std::path::Path;
+
+        use super::Format;
+
+        let path = Path::new("themes/app.toml");
+        let err = Format::Toml
+            .parse("info = \"cyna\"\nwarning = \"yellow blue\"", Some(path))
+            .unwrap_err();
+
+        assert_eq!(err.path(), Some(path));
+        assert_eq!(
+            err.to_string(),
+            concat!(
+                "themes/app.toml: invalid theme entry \"info\": unknown color or ",
+                "attribute name \"cyna\" at position 0; did you mean \"cyan\"?\n",
+                "themes/app.toml: invalid theme entry \"warning\": unexpected token ",
+                "\"blue\" at position 7",
+            )
+        );
+    }
+
+    #[test]
+    fn test_load_unsupported_or_missing_file() {
+        let err = Theme::load("theme.yaml").unwrap_err();
+        assert!(matches!(err.kind(), LoadThemeErrorKind::UnsupportedFormat));
+        assert_eq!(
+            err.to_string(),
+            "theme.yaml: unsupported theme file format (expected .toml or .json)"
+        );
+
+        #[cfg(feature = "toml")]
+        {
+            let err = Theme::load("does/not/exist.toml").unwrap_err();
+            assert!(matches!(err.kind(), LoadThemeErrorKind::Io(_)));
+        }
+    }
+}